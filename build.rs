@@ -0,0 +1,153 @@
+// generates the `Element` enum and its metadata table from `elements-bonds.csv`
+
+use std::{env, fmt::Write, fs, path::Path};
+
+struct Row {
+    n: u32,
+    symbol: String,
+    name: String,
+    // the `bonds` column, which is the electron rule `octet-rule.py` assigned
+    electron_rule: u32,
+    mass: f64,
+}
+
+fn period(n: u32) -> u32 {
+    match n {
+        1..=2 => 1,
+        3..=10 => 2,
+        11..=18 => 3,
+        19..=36 => 4,
+        37..=54 => 5,
+        55..=86 => 6,
+        _ => 7,
+    }
+}
+
+// the noble gas that closes the previous period
+fn core(period: u32) -> u32 {
+    [0, 2, 10, 18, 36, 54, 86][period as usize - 1]
+}
+
+// lanthanides and actinides have no group
+fn group(n: u32) -> Option<u32> {
+    let period = period(n);
+    let position = n - core(period);
+    match period {
+        1 if n == 1 => Some(1),
+        1 => Some(18),
+        2 | 3 if position > 2 => Some(position + 10),
+        2 | 3 => Some(position),
+        4 | 5 => Some(position),
+        _ if position <= 2 => Some(position),
+        _ if position <= 17 => None,
+        _ => Some(position - 14),
+    }
+}
+
+// the C header for the functions in `src/capi.rs`, in OUT_DIR so building never touches the checkout.
+// `include/esoteric_reaction.h` is the same header, regenerated by running cbindgen with `cbindgen.toml`.
+#[cfg(feature = "capi")]
+fn header() {
+    use cbindgen::Config;
+
+    println!("cargo:rerun-if-changed=src/capi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    let config = Config::from_file("cbindgen.toml").expect("could not read cbindgen.toml");
+    let dest = Path::new(&env::var("OUT_DIR").unwrap()).join("esoteric_reaction.h");
+    cbindgen::Builder::new()
+        .with_crate(env::var("CARGO_MANIFEST_DIR").unwrap())
+        .with_config(config)
+        .generate()
+        .expect("could not generate the C header")
+        .write_to_file(dest);
+}
+
+fn main() {
+    #[cfg(feature = "capi")]
+    header();
+
+    println!("cargo:rerun-if-changed=elements-bonds.csv");
+
+    let csv = fs::read_to_string("elements-bonds.csv").expect("could not read elements-bonds.csv");
+    let rows: Vec<Row> = csv
+        .lines()
+        .skip(1) // header
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+            let [n, symbol, name, bonds, mass] = fields[..] else {
+                panic!("expected `n,symbol,name,bonds,mass`, got {line}")
+            };
+            Row {
+                n: n.parse()
+                    .unwrap_or_else(|_| panic!("bad atomic number in {line}")),
+                symbol: symbol.to_string(),
+                name: name.to_string(),
+                electron_rule: bonds
+                    .parse()
+                    .unwrap_or_else(|_| panic!("bad bonds in {line}")),
+                mass: mass
+                    .parse()
+                    .unwrap_or_else(|_| panic!("bad mass in {line}")),
+            }
+        })
+        .collect();
+
+    let mut out = String::new();
+    out.push_str("// generated by build.rs from elements-bonds.csv, do not edit\n\n");
+
+    out.push_str(
+        "#[derive(Clone, PartialEq, Eq, Hash)]\npub enum Element {\n    Heavy(String),\n    Anti(Box<Element>),\n",
+    );
+    for row in &rows {
+        writeln!(out, "    {},", row.symbol).unwrap();
+    }
+    out.push_str("}\n\n");
+
+    writeln!(
+        out,
+        "pub static ELEMENTS: [ElementInfo; {}] = [",
+        rows.len()
+    )
+    .unwrap();
+    for row in &rows {
+        let group = match group(row.n) {
+            Some(g) => format!("Some({g})"),
+            None => "None".to_string(),
+        };
+        writeln!(
+            out,
+            "    ElementInfo {{ number: {}, symbol: {:?}, name: {:?}, electron_rule: {}, group: {}, period: {}, mass: {:?} }},",
+            row.n,
+            row.symbol,
+            row.name,
+            row.electron_rule,
+            group,
+            period(row.n),
+            row.mass
+        )
+        .unwrap();
+    }
+    out.push_str("];\n\n");
+
+    out.push_str("impl Element {\n");
+    out.push_str("    fn from_known_number(n: i64) -> Option<Element> {\n        match n {\n");
+    for row in &rows {
+        writeln!(
+            out,
+            "            {} => Some(Element::{}),",
+            row.n, row.symbol
+        )
+        .unwrap();
+    }
+    out.push_str("            _ => None,\n        }\n    }\n\n");
+    out.push_str("    fn table_index(&self) -> Option<usize> {\n        match self {\n");
+    out.push_str("            Element::Heavy(_) | Element::Anti(_) => None,\n");
+    for (i, row) in rows.iter().enumerate() {
+        writeln!(out, "            Element::{} => Some({i}),", row.symbol).unwrap();
+    }
+    out.push_str("        }\n    }\n}\n");
+
+    let dest = Path::new(&env::var("OUT_DIR").unwrap()).join("elements.rs");
+    fs::write(dest, out).expect("could not write generated elements");
+}
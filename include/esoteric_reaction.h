@@ -0,0 +1,150 @@
+// generated from src/capi.rs by cbindgen with cbindgen.toml, do not edit
+
+#ifndef ESOTERIC_REACTION_H
+#define ESOTERIC_REACTION_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The type of a value.
+ */
+typedef enum ErKind {
+  ER_KIND_NUMBER,
+  ER_KIND_RATIONAL,
+  ER_KIND_BOOLEAN,
+  ER_KIND_STRING,
+  ER_KIND_PAIR,
+  ER_KIND_LIST,
+  ER_KIND_MAP,
+} ErKind;
+
+/**
+ * How a run went.
+ */
+typedef enum ErStatus {
+  ER_STATUS_OK,
+  /**
+   * A null pointer, or a string that isn't UTF-8. Nothing ran.
+   */
+  ER_STATUS_INVALID_ARGUMENT,
+  /**
+   * The input wasn't a JSON array of values or functional groups. Nothing ran.
+   */
+  ER_STATUS_INPUT,
+  /**
+   * The program didn't parse. Nothing ran.
+   */
+  ER_STATUS_PARSE,
+  /**
+   * The program stopped partway through. Its output up to there is kept.
+   */
+  ER_STATUS_RUNTIME,
+  /**
+   * The interpreter crashed. Its output up to there is kept.
+   */
+  ER_STATUS_PANIC,
+} ErStatus;
+
+/**
+ * The result of a run: what it printed, why it stopped, and the values left on the current carbon.
+ */
+typedef struct ErRun ErRun;
+
+/**
+ * A value left on the tape, and everything inside it.
+ */
+typedef struct ErValue ErValue;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Runs a program on a fresh tape.
+ * `input` is a JSON array of values or functional groups to bond to the tape first, like `er run --input-json`,
+ * or null for none.
+ * Unless the status is `ER_STATUS_INVALID_ARGUMENT`, `*run` is set to a result to free with `er_run_free`.
+ */
+enum ErStatus er_run(const char *source,
+                     const char *input,
+                     struct ErRun **run);
+
+/**
+ * What the program printed, NUL-terminated. If `len` isn't null, it is set to the length without the NUL.
+ */
+const char *er_run_output(const struct ErRun *run,
+                          size_t *len);
+
+/**
+ * Why the run stopped, or null if it didn't.
+ */
+const char *er_run_error(const struct ErRun *run);
+
+/**
+ * The number of values bonded to the current carbon when the run stopped.
+ */
+size_t er_run_value_count(const struct ErRun *run);
+
+/**
+ * A value bonded to the current carbon, in the order they were bonded, or null if `i` is out of range.
+ */
+const struct ErValue *er_run_value(const struct ErRun *run,
+                                   size_t i);
+
+/**
+ * Frees a run, and every value and string borrowed from it. Null is ignored.
+ */
+void er_run_free(struct ErRun *run);
+
+/**
+ * Which of the functions below apply to a value.
+ */
+enum ErKind er_value_kind(const struct ErValue *value);
+
+/**
+ * The value as `Kr` prints it, so strings are their contents, NUL-terminated.
+ * If `len` isn't null, it is set to the length without the NUL.
+ */
+const char *er_value_text(const struct ErValue *value, size_t *len);
+
+/**
+ * Sets `*out` to a number, and returns whether it is one that fits in 64 bits.
+ */
+bool er_value_integer(const struct ErValue *value, int64_t *out);
+
+/**
+ * Sets `*numerator` and `*denominator` to a rational, or a number over 1,
+ * and returns whether it is one with both parts fitting in 64 bits.
+ */
+bool er_value_rational(const struct ErValue *value, int64_t *numerator, int64_t *denominator);
+
+/**
+ * Sets `*out` to a boolean, and returns whether it is one.
+ */
+bool er_value_boolean(const struct ErValue *value, bool *out);
+
+/**
+ * The number of items in a list, entries in a map, or 2 for a pair. 0 for anything else.
+ */
+size_t er_value_len(const struct ErValue *value);
+
+/**
+ * An item of a list, the value of a map entry, or the left (0) or right (1) of a pair.
+ * Null if `i` is out of range.
+ */
+const struct ErValue *er_value_item(const struct ErValue *value, size_t i);
+
+/**
+ * The key of a map entry, or null if `value` isn't a map or `i` is out of range.
+ */
+const struct ErValue *er_value_key(const struct ErValue *value, size_t i);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* ESOTERIC_REACTION_H */
@@ -8,17 +8,18 @@ class Element:
     name: str
     symbol: str
     bonds: int
+    mass: str
 
 
 data: list[Element] = []
 header: list[str] = []
 
 with open("elements.csv", "r") as file:
-    reader = csv.DictReader(file, delimiter=',', fieldnames=["n", "symbol", "name", "bonds"])
-    h = next(reader, {})
-    header = [k for k in h.keys()]
+    reader = csv.DictReader(file, delimiter=',', fieldnames=["n", "symbol", "name", "mass"])
+    next(reader, None)  # header
+    header = ["n", "symbol", "name", "bonds", "mass"]
     for d in reader:
-        data.append(Element(n=int(d['n']), symbol=d['symbol'], name=d['name'], bonds=0))
+        data.append(Element(n=int(d['n']), symbol=d['symbol'], name=d['name'], bonds=0, mass=d['mass']))
 
 for i, e in enumerate(data):
     if 1 <= e.n <= 3:
@@ -38,4 +39,4 @@ with open("elements-bonds.csv", "w") as file:
     writer = csv.writer(file)
     writer.writerow(header)
     for d in data:
-        writer.writerow([d.n, d.symbol, d.name, d.bonds])
+        writer.writerow([d.n, d.symbol, d.name, d.bonds, d.mass])
@@ -0,0 +1,392 @@
+// C functions for running programs, built into the `cdylib` with the `capi` feature.
+// `include/esoteric_reaction.h` is generated from this file with cbindgen (see `cbindgen.toml`),
+// which copies `///` comments into the header, so those are written for C programmers.
+//
+// every function follows the same rules, instead of each having a safety section:
+// strings passed in are NUL-terminated UTF-8, handles passed in came from this library and weren't freed,
+// and everything returned is borrowed from the `ErRun` it came from, until `er_run_free`.
+#![allow(clippy::missing_safety_doc)]
+
+use std::{
+    ffi::{c_char, CStr, CString},
+    panic::{self, AssertUnwindSafe},
+    ptr,
+};
+
+use crate::{
+    error::Error,
+    eval::{traits::Valuable, value::Value},
+    import::json::groups_from_json,
+    run::interpreter::Interpreter,
+};
+
+/// How a run went.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErStatus {
+    Ok,
+    /// A null pointer, or a string that isn't UTF-8. Nothing ran.
+    InvalidArgument,
+    /// The input wasn't a JSON array of values or functional groups. Nothing ran.
+    Input,
+    /// The program didn't parse. Nothing ran.
+    Parse,
+    /// The program stopped partway through. Its output up to there is kept.
+    Runtime,
+    /// The interpreter crashed. Its output up to there is kept.
+    Panic,
+}
+
+/// The type of a value.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErKind {
+    Number,
+    Rational,
+    Boolean,
+    String,
+    Pair,
+    List,
+    Map,
+}
+
+/// The result of a run: what it printed, why it stopped, and the values left on the current carbon.
+pub struct ErRun {
+    // NUL-terminated, though the output itself may have NULs in it
+    output: Vec<u8>,
+    error: Option<CString>,
+    values: Vec<ErValue>,
+}
+
+/// A value left on the tape, and everything inside it.
+pub struct ErValue {
+    value: Value,
+    // the value as `Kr` prints it, NUL-terminated
+    text: Vec<u8>,
+    // list items, the halves of a pair, or map values, with map keys alongside
+    items: Vec<ErValue>,
+    keys: Vec<ErValue>,
+}
+
+impl ErValue {
+    fn new(value: Value) -> ErValue {
+        let mut text = match &value {
+            Value::String(s) => s.iter().collect::<String>().into_bytes(),
+            v => v.to_string().into_bytes(),
+        };
+        text.push(0);
+        let (keys, items) = match &value {
+            Value::Pair(l, r) => (vec![], vec![*l.clone(), *r.clone()]),
+            Value::List(v) => (vec![], v.clone()),
+            Value::Map(m) => m.clone().into_iter().unzip(),
+            _ => (vec![], vec![]),
+        };
+        ErValue {
+            value,
+            text,
+            items: items.into_iter().map(ErValue::new).collect(),
+            keys: keys.into_iter().map(ErValue::new).collect(),
+        }
+    }
+}
+
+unsafe fn str_arg<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+    CStr::from_ptr(s).to_str().ok()
+}
+
+fn message(s: String) -> Option<CString> {
+    CString::new(s.replace('\0', "")).ok()
+}
+
+/// Runs a program on a fresh tape.
+/// `input` is a JSON array of values or functional groups to bond to the tape first, like `er run --input-json`,
+/// or null for none.
+/// Unless the status is `ER_STATUS_INVALID_ARGUMENT`, `*run` is set to a result to free with `er_run_free`.
+#[no_mangle]
+pub unsafe extern "C" fn er_run(
+    source: *const c_char,
+    input: *const c_char,
+    run: *mut *mut ErRun,
+) -> ErStatus {
+    if run.is_null() {
+        return ErStatus::InvalidArgument;
+    }
+    *run = ptr::null_mut();
+    let Some(source) = str_arg(source) else {
+        return ErStatus::InvalidArgument;
+    };
+    let input = match (input.is_null(), str_arg(input)) {
+        (true, _) => None,
+        (false, Some(input)) => Some(input),
+        (false, None) => return ErStatus::InvalidArgument,
+    };
+    let mut result = ErRun {
+        output: Vec::new(),
+        error: None,
+        values: Vec::new(),
+    };
+    let status = result.run(source, input);
+    result.output.push(0);
+    *run = Box::into_raw(Box::new(result));
+    status
+}
+
+impl ErRun {
+    // loading the input, running, and collecting the values are all behind `catch_unwind`,
+    // so a panic anywhere in them is a status instead of unwinding into C
+    fn run(&mut self, source: &str, input: Option<&str>) -> ErStatus {
+        let ran = panic::catch_unwind(AssertUnwindSafe(|| self.try_run(source, input)));
+        let (status, error) = match ran {
+            Ok(ran) => ran,
+            Err(payload) => {
+                let reason = match (
+                    payload.downcast_ref::<&str>(),
+                    payload.downcast_ref::<String>(),
+                ) {
+                    (Some(s), _) => s.to_string(),
+                    (_, Some(s)) => s.clone(),
+                    _ => String::from("the interpreter panicked"),
+                };
+                (ErStatus::Panic, Some(reason))
+            }
+        };
+        self.error = error.and_then(message);
+        status
+    }
+
+    fn try_run(&mut self, source: &str, input: Option<&str>) -> (ErStatus, Option<String>) {
+        let groups = match input.map(groups_from_json).transpose() {
+            Ok(groups) => groups.unwrap_or_default(),
+            Err(err) => return (ErStatus::Input, Some(err.to_string())),
+        };
+        let mut interpreter = Interpreter::new(&mut self.output);
+        groups
+            .into_iter()
+            .for_each(|fg| interpreter.tape.add_functional_group(fg));
+        let ran = interpreter.run(source);
+        let values: Vec<Value> = interpreter
+            .bonded_groups()
+            .iter()
+            .map(|fg| fg.value())
+            .collect();
+        self.values = values.into_iter().map(ErValue::new).collect();
+        match ran {
+            Ok(()) => (ErStatus::Ok, None),
+            Err(err @ Error::Parse(_)) => (ErStatus::Parse, Some(err.to_string())),
+            Err(err @ Error::Runtime(_)) => (ErStatus::Runtime, Some(err.to_string())),
+        }
+    }
+}
+
+/// What the program printed, NUL-terminated. If `len` isn't null, it is set to the length without the NUL.
+#[no_mangle]
+pub unsafe extern "C" fn er_run_output(run: *const ErRun, len: *mut usize) -> *const c_char {
+    let run = &*run;
+    if !len.is_null() {
+        *len = run.output.len() - 1;
+    }
+    run.output.as_ptr() as *const c_char
+}
+
+/// Why the run stopped, or null if it didn't.
+#[no_mangle]
+pub unsafe extern "C" fn er_run_error(run: *const ErRun) -> *const c_char {
+    match &(&*run).error {
+        Some(error) => error.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+/// The number of values bonded to the current carbon when the run stopped.
+#[no_mangle]
+pub unsafe extern "C" fn er_run_value_count(run: *const ErRun) -> usize {
+    (&*run).values.len()
+}
+
+/// A value bonded to the current carbon, in the order they were bonded, or null if `i` is out of range.
+#[no_mangle]
+pub unsafe extern "C" fn er_run_value(run: *const ErRun, i: usize) -> *const ErValue {
+    match (&*run).values.get(i) {
+        Some(value) => value,
+        None => ptr::null(),
+    }
+}
+
+/// Frees a run, and every value and string borrowed from it. Null is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn er_run_free(run: *mut ErRun) {
+    if !run.is_null() {
+        drop(Box::from_raw(run));
+    }
+}
+
+/// Which of the functions below apply to a value.
+#[no_mangle]
+pub unsafe extern "C" fn er_value_kind(value: *const ErValue) -> ErKind {
+    match (&*value).value {
+        Value::Number(_) => ErKind::Number,
+        Value::Rational(_) => ErKind::Rational,
+        Value::Boolean(_) => ErKind::Boolean,
+        Value::String(_) => ErKind::String,
+        Value::Pair(..) => ErKind::Pair,
+        Value::List(_) => ErKind::List,
+        Value::Map(_) => ErKind::Map,
+    }
+}
+
+/// The value as `Kr` prints it, so strings are their contents, NUL-terminated.
+/// If `len` isn't null, it is set to the length without the NUL.
+#[no_mangle]
+pub unsafe extern "C" fn er_value_text(value: *const ErValue, len: *mut usize) -> *const c_char {
+    let value = &*value;
+    if !len.is_null() {
+        *len = value.text.len() - 1;
+    }
+    value.text.as_ptr() as *const c_char
+}
+
+/// Sets `*out` to a number, and returns whether it is one that fits in 64 bits.
+#[no_mangle]
+pub unsafe extern "C" fn er_value_integer(value: *const ErValue, out: *mut i64) -> bool {
+    let Value::Number(n) = &(&*value).value else {
+        return false;
+    };
+    match n.to_string().parse() {
+        Ok(n) => {
+            *out = n;
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// Sets `*numerator` and `*denominator` to a rational, or a number over 1,
+/// and returns whether it is one with both parts fitting in 64 bits.
+#[no_mangle]
+pub unsafe extern "C" fn er_value_rational(
+    value: *const ErValue,
+    numerator: *mut i64,
+    denominator: *mut i64,
+) -> bool {
+    let (n, d) = match &(&*value).value {
+        Value::Number(n) => (n.to_string(), String::from("1")),
+        Value::Rational(r) => (r.numer().to_string(), r.denom().to_string()),
+        _ => return false,
+    };
+    match (n.parse(), d.parse()) {
+        (Ok(n), Ok(d)) => {
+            *numerator = n;
+            *denominator = d;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Sets `*out` to a boolean, and returns whether it is one.
+#[no_mangle]
+pub unsafe extern "C" fn er_value_boolean(value: *const ErValue, out: *mut bool) -> bool {
+    match (&*value).value {
+        Value::Boolean(b) => {
+            *out = b;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// The number of items in a list, entries in a map, or 2 for a pair. 0 for anything else.
+#[no_mangle]
+pub unsafe extern "C" fn er_value_len(value: *const ErValue) -> usize {
+    (&*value).items.len()
+}
+
+/// An item of a list, the value of a map entry, or the left (0) or right (1) of a pair.
+/// Null if `i` is out of range.
+#[no_mangle]
+pub unsafe extern "C" fn er_value_item(value: *const ErValue, i: usize) -> *const ErValue {
+    match (&*value).items.get(i) {
+        Some(item) => item,
+        None => ptr::null(),
+    }
+}
+
+/// The key of a map entry, or null if `value` isn't a map or `i` is out of range.
+#[no_mangle]
+pub unsafe extern "C" fn er_value_key(value: *const ErValue, i: usize) -> *const ErValue {
+    match (&*value).keys.get(i) {
+        Some(key) => key,
+        None => ptr::null(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the status of running `source` on `input`, and why it stopped, if it did
+    fn run(source: &str, input: Option<&str>) -> (ErStatus, Option<String>, String) {
+        let source = CString::new(source).unwrap();
+        let input = input.map(|input| CString::new(input).unwrap());
+        let mut run = ptr::null_mut();
+        unsafe {
+            let status = er_run(
+                source.as_ptr(),
+                input.as_ref().map_or(ptr::null(), |input| input.as_ptr()),
+                &mut run,
+            );
+            let error = er_run_error(run);
+            let error =
+                (!error.is_null()).then(|| CStr::from_ptr(error).to_str().unwrap().to_string());
+            let output = CStr::from_ptr(er_run_output(run, ptr::null_mut()))
+                .to_str()
+                .unwrap()
+                .to_string();
+            er_run_free(run);
+            (status, error, output)
+        }
+    }
+
+    #[test]
+    fn runs_programs() {
+        assert_eq!(
+            run("H^3Xn -> H\n", Some("[1]")),
+            (ErStatus::Ok, None, String::from("1\n3\n"))
+        );
+    }
+
+    #[test]
+    fn reports_errors_by_status() {
+        assert_eq!(run("H^3Xn -> H", None).0, ErStatus::Parse);
+        let (status, error, output) = run("H^3Xn + Zz -> H\n", None);
+        assert_eq!((status, output.as_str()), (ErStatus::Runtime, "3\n"));
+        assert!(error.unwrap().contains("`Zz`"));
+        assert_eq!(run("Xn -> H\n", Some("{}")).0, ErStatus::Input);
+    }
+
+    #[test]
+    fn malformed_groups_are_input_errors() {
+        let groups = [
+            r#"[{"kind": "Amine", "atoms": ["N"], "bonds": [], "head": 0}]"#,
+            r#"[{"kind": "Sulfide", "atoms": ["S", "N"], "bonds": [[0, 1]], "head": 0}]"#,
+            r#"[{"kind": "Ester", "atoms": ["C", "O", "O", "Li", "-Li"],
+                 "bonds": [[0, 1], [0, 2], [2, 3], [2, 4]], "head": 0, "carbonyl": 1, "alkoxy": 2}]"#,
+        ];
+        for input in groups {
+            let (status, error, _) = run("Xn -> H\n", Some(input));
+            assert_eq!(status, ErStatus::Input, "{input}: {error:?}");
+        }
+    }
+
+    #[test]
+    fn header_is_up_to_date() {
+        assert_eq!(
+            include_str!(concat!(env!("OUT_DIR"), "/esoteric_reaction.h")),
+            include_str!("../include/esoteric_reaction.h"),
+            "regenerate include/esoteric_reaction.h with cbindgen, see cbindgen.toml"
+        );
+    }
+}
@@ -0,0 +1,31 @@
+use std::fmt::Display;
+
+use crate::{par::parse_error::ParseError, run::runtime_error::RuntimeError};
+
+// anything that can go wrong running a program from its source
+#[derive(Debug)]
+pub enum Error {
+    Parse(ParseError),
+    Runtime(RuntimeError),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Parse(err) => write!(f, "{err}"),
+            Error::Runtime(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        Error::Parse(err)
+    }
+}
+
+impl From<RuntimeError> for Error {
+    fn from(err: RuntimeError) -> Self {
+        Error::Runtime(err)
+    }
+}
@@ -1,15 +1,44 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use petgraph::stable_graph::{EdgeIndex, NodeIndex, StableUnGraph};
+use petgraph::{
+    algo::is_isomorphic_matching,
+    stable_graph::{EdgeIndex, NodeIndex, StableUnGraph},
+    Graph, Undirected,
+};
 
-use super::{atom_like::AtomLike, molecule::Molecule};
+use super::{atom_like::AtomLike, element::Element, molecule::Molecule};
 
 pub type AtomGraph = StableUnGraph<Molecule, ()>;
 
+// where an atom of an expansion came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Origin {
+    // the `Molecule::F` nodes leading from the outermost group down to the atom's group,
+    // each indexing into the group before it. empty for atoms of the outermost group.
+    pub path: Vec<NodeIndex>,
+    // the atom's index inside its own group
+    pub idx: NodeIndex,
+}
+
+// a molecule with every nested functional group inlined, see `Atoms::expand`
+#[derive(Debug, Clone)]
+pub struct Expansion {
+    pub atoms: Atoms,
+    pub origins: HashMap<NodeIndex, Origin>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Atoms {
     atoms: AtomGraph,
     pub head: NodeIndex,
+    // filler hydrogens that have been materialised into the graph
+    fillers: HashSet<NodeIndex>,
+}
+
+impl Default for Atoms {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Atoms {
@@ -17,6 +46,16 @@ impl Atoms {
         Atoms {
             atoms: StableUnGraph::default(),
             head: NodeIndex::default(),
+            fillers: HashSet::new(),
+        }
+    }
+
+    // atoms around a graph that was built some other way, without any fillers
+    pub fn with_graph(atoms: AtomGraph, head: NodeIndex) -> Self {
+        Atoms {
+            atoms,
+            head,
+            fillers: HashSet::new(),
         }
     }
 
@@ -26,57 +65,198 @@ impl Atoms {
     pub fn mut_atoms(&mut self) -> &mut AtomGraph {
         &mut self.atoms
     }
+
+    // the atoms bonded to `idx`, skipping any materialised filler hydrogens
+    pub fn neighbors(&self, idx: NodeIndex) -> impl Iterator<Item = NodeIndex> + '_ {
+        self.atoms
+            .neighbors(idx)
+            .filter(|neighbor| !self.is_filler(*neighbor))
+    }
+
+    pub fn is_filler(&self, idx: NodeIndex) -> bool {
+        self.fillers.contains(&idx)
+    }
+
+    pub fn set_filler(&mut self, idx: NodeIndex) {
+        self.fillers.insert(idx);
+    }
+
+    // the number of filler hydrogens needed to saturate the atom at `idx`.
+    // a group that is `attached` to something (a carbon, or the leading `H` of a literal)
+    // spends one of its head's bonds on it, so `HC_(n)H_(2n+1)` works out.
+    // the tape and other molecules standing on their own have nothing to spend it on.
+    // functional groups carry their own hydrogens, so they never need any here.
+    pub fn implicit_hydrogens(&self, idx: NodeIndex, attached: bool) -> usize {
+        let Some(Molecule::E(e)) = self.atoms.node_weight(idx) else {
+            return 0;
+        };
+        let parent_bond = if attached && idx == self.head { 1 } else { 0 };
+        let used = self.atoms.neighbors(idx).count() + parent_bond;
+        e.bond_capacity().saturating_sub(used)
+    }
+
+    // the number of filler hydrogens in this entire molecule, including nested groups,
+    // which are always attached to the atom they are bonded to
+    pub fn total_implicit_hydrogens(&self, attached: bool) -> usize {
+        self.atoms
+            .node_indices()
+            .map(|idx| match self.atoms.node_weight(idx) {
+                Some(Molecule::F(fg)) => fg.get_atoms().total_implicit_hydrogens(true),
+                _ => self.implicit_hydrogens(idx, attached),
+            })
+            .sum()
+    }
+
+    // a copy of this molecule with every filler hydrogen added as an actual atom,
+    // recursing into nested groups. fillers are still ignored when weighing.
+    pub fn with_implicit_hydrogens(&self, attached: bool) -> Atoms {
+        let mut saturated = self.clone();
+        let node_indices: Vec<NodeIndex> = self.atoms.node_indices().collect();
+        for idx in node_indices {
+            if let Some(Molecule::F(fg)) = saturated.atoms.node_weight_mut(idx) {
+                let child = fg.get_atoms().with_implicit_hydrogens(true);
+                *fg.get_atoms_mut() = child;
+                continue;
+            }
+            for _ in 0..self.implicit_hydrogens(idx, attached) {
+                let h = saturated.atoms.add_node(Molecule::E(Element::H));
+                saturated.atoms.add_edge(idx, h, ());
+                saturated.fillers.insert(h);
+            }
+        }
+        saturated
+    }
+
+    // inline every nested functional group, recursively, so only elements are left.
+    // a bond to a functional group becomes a bond to that group's head,
+    // and materialised filler hydrogens stay fillers.
+    pub fn expand(&self) -> Expansion {
+        let mut expansion = Expansion {
+            atoms: Atoms::new(),
+            origins: HashMap::new(),
+        };
+        if let Some(head) = self.expand_into(&mut expansion, &[]) {
+            expansion.atoms.head = head;
+        }
+        expansion
+    }
+
+    // add this group's atoms to `expansion`, returning where its head ended up.
+    // empty groups have no head, and anything bonded to them loses that bond.
+    fn expand_into(&self, expansion: &mut Expansion, path: &[NodeIndex]) -> Option<NodeIndex> {
+        let mut indexes: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+        for idx in self.atoms.node_indices() {
+            match self.atoms.node_weight(idx).unwrap() {
+                Molecule::E(e) => {
+                    let new_idx = expansion.atoms.atoms.add_node(Molecule::E(e.clone()));
+                    if self.is_filler(idx) {
+                        expansion.atoms.fillers.insert(new_idx);
+                    }
+                    let origin = Origin {
+                        path: path.to_vec(),
+                        idx,
+                    };
+                    expansion.origins.insert(new_idx, origin);
+                    indexes.insert(idx, new_idx);
+                }
+                Molecule::F(fg) => {
+                    let path = [path, &[idx]].concat();
+                    if let Some(child_head) = fg.get_atoms().expand_into(expansion, &path) {
+                        indexes.insert(idx, child_head);
+                    }
+                }
+            }
+        }
+        for edge in self.atoms.edge_indices() {
+            let (l, r) = self.atoms.edge_endpoints(edge).unwrap();
+            if let (Some(&l), Some(&r)) = (indexes.get(&l), indexes.get(&r)) {
+                expansion.atoms.add_edge(l, r);
+            }
+        }
+        indexes.get(&self.head).copied()
+    }
+
+    // a copy of everything reachable from `idx` without going back through `parent`,
+    // with `idx` as the head, and where each atom was copied to.
+    // filler hydrogens are left behind, since they can be worked out again.
+    pub fn branch(
+        &self,
+        idx: NodeIndex,
+        parent: Option<NodeIndex>,
+    ) -> (Atoms, HashMap<NodeIndex, NodeIndex>) {
+        let mut branch = Atoms::new();
+        let mut copies: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+        let mut stack = vec![(idx, parent)];
+        while let Some((idx, parent)) = stack.pop() {
+            if copies.contains_key(&idx) {
+                continue;
+            }
+            let copy = branch.add_node(self.atoms.node_weight(idx).unwrap().clone());
+            copies.insert(idx, copy);
+            stack.extend(
+                self.neighbors(idx)
+                    .filter(|n| Some(*n) != parent)
+                    .map(|n| (n, Some(idx))),
+            );
+        }
+        for edge in self.atoms.edge_indices() {
+            let (l, r) = self.atoms.edge_endpoints(edge).unwrap();
+            if let (Some(&l), Some(&r)) = (copies.get(&l), copies.get(&r)) {
+                branch.add_edge(l, r);
+            }
+        }
+        (branch, copies)
+    }
+
+    // whether both molecules have the same shape, atom for atom.
+    // nested groups are compared the same way, and the heads have to line up,
+    // so `ON` and `NO` are different even though they weigh the same.
+    // filler hydrogens are ignored, like when weighing.
+    pub fn is_isomorphic(&self, other: &Atoms) -> bool {
+        self.atoms.node_count() - self.fillers.len()
+            == other.atoms.node_count() - other.fillers.len()
+            && is_isomorphic_matching(
+                &self.skeleton(),
+                &other.skeleton(),
+                |(l_head, l), (r_head, r)| l_head == r_head && l == r,
+                |_, _| true,
+            )
+    }
+
+    // a compact copy of the graph without fillers, with each atom tagged with whether it is the head
+    fn skeleton(&self) -> Graph<(bool, &Molecule), (), Undirected> {
+        let mut skeleton = Graph::new_undirected();
+        let mut indexes: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+        for idx in self
+            .atoms
+            .node_indices()
+            .filter(|idx| !self.is_filler(*idx))
+        {
+            let m = self.atoms.node_weight(idx).unwrap();
+            indexes.insert(idx, skeleton.add_node((idx == self.head, m)));
+        }
+        for edge in self.atoms.edge_indices() {
+            let (l, r) = self.atoms.edge_endpoints(edge).unwrap();
+            if let (Some(&l), Some(&r)) = (indexes.get(&l), indexes.get(&r)) {
+                skeleton.add_edge(l, r, ());
+            }
+        }
+        skeleton
+    }
 }
 
 impl AtomLike for Atoms {
     fn get_atoms(&self) -> &Atoms {
-        &self
+        self
     }
 
     fn get_atoms_mut(&mut self) -> &mut Atoms {
         self
     }
 
-    // recursively flatten this group of atoms by attaching the nodes of child molecules to their parent
+    // recursively flatten this group of atoms into elements only
     fn flatten(&self) -> Atoms {
-        let mut flattened_atoms = self.clone();
-        for node_idx in self.atoms.node_indices() {
-            // for each neighbor...
-            for neighbor_idx in self.atoms.neighbors(node_idx) {
-                let neighbor = self.atoms.node_weight(neighbor_idx);
-                // recursively flatten non-elemental molecules
-                if let Some(Molecule::F(fg)) = &neighbor {
-                    let child = fg.flatten();
-                    let mut child_to_parent_indexes: HashMap<NodeIndex, NodeIndex> = HashMap::new();
-                    // for each atom in the child, add it to the parent's graph
-                    for child_idx in child.atoms.node_indices() {
-                        let child_atom = child.atoms.node_weight(child_idx).unwrap().clone();
-                        let parent_idx = flattened_atoms.add_node(child_atom);
-                        // keep track of the mappings from child indexes to parent indexes
-                        child_to_parent_indexes.insert(child_idx, parent_idx);
-                    }
-                    // rebuild all the edges of the child graph into the parent
-                    for edge_idx in child.atoms.edge_indices() {
-                        // get child edge
-                        let edge = child.atoms.edge_endpoints(edge_idx);
-                        if let Some((l_child, r_child)) = edge {
-                            // get the new indexes of these nodes
-                            let l_parent = child_to_parent_indexes.get(&l_child);
-                            let r_parent = child_to_parent_indexes.get(&r_child);
-                            if let (Some(&l), Some(&r)) = (l_parent, r_parent) {
-                                flattened_atoms.add_edge(l, r);
-                            }
-                        }
-                    }
-                    // remove the old node
-                    flattened_atoms.atoms.remove_node(neighbor_idx);
-                    // connect the head of the new child nodes to the current node
-                    let new_child_head = child_to_parent_indexes.get(&child.head).unwrap().clone();
-                    flattened_atoms.add_edge(node_idx, new_child_head);
-                }
-            }
-        }
-        flattened_atoms
+        self.expand().atoms
     }
 
     // add a molecule to this grouping of atoms.
@@ -94,3 +274,155 @@ impl AtomLike for Atoms {
         self.atoms.add_edge(m, n, ())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::{
+        functional_groups::{alkane::Alkane, ether::Ether, FunctionalGroup},
+        value::Value,
+    };
+
+    // a carbon bonded to `n` fluorines
+    fn fluorinated(n: usize) -> Atoms {
+        let mut atoms = Atoms::new();
+        let c = atoms.add_node(Molecule::E(Element::C));
+        for _ in 0..n {
+            let f = atoms.add_node(Molecule::E(Element::F));
+            atoms.add_edge(c, f);
+        }
+        atoms
+    }
+
+    #[test]
+    fn counts_an_alkane() {
+        // 3 carbons
+        let alk = Alkane::new_n_alkane(2);
+        let atoms = alk.get_atoms();
+        let counts: Vec<usize> = alk
+            .backbone()
+            .map(|c| atoms.implicit_hydrogens(*c, true))
+            .collect();
+        assert_eq!(counts, [2, 2, 3]);
+        // C3H7 bonded to something, or C3H8 on its own
+        assert_eq!(atoms.total_implicit_hydrogens(true), 7);
+        assert_eq!(atoms.total_implicit_hydrogens(false), 8);
+    }
+
+    #[test]
+    fn counts_an_ether() {
+        // `HOLi`: the oxygen spends its bonds on the leading `H` and the lithium
+        let ether = Ether::from(3);
+        let atoms = ether.get_atoms();
+        assert_eq!(atoms.implicit_hydrogens(atoms.head, true), 0);
+        assert_eq!(atoms.total_implicit_hydrogens(true), 0);
+        // `OLi` on its own is `[OH][Li]`
+        assert_eq!(atoms.total_implicit_hydrogens(false), 1);
+    }
+
+    #[test]
+    fn counts_nothing_on_a_full_carbon() {
+        let full = fluorinated(4);
+        assert_eq!(full.implicit_hydrogens(full.head, true), 0);
+        assert_eq!(full.implicit_hydrogens(full.head, false), 0);
+        let three = fluorinated(3);
+        assert_eq!(three.implicit_hydrogens(three.head, true), 0);
+        assert_eq!(three.implicit_hydrogens(three.head, false), 1);
+        // fluorine only has the one bond, which it spends on the carbon
+        let f = three.neighbors(three.head).next().unwrap();
+        assert_eq!(three.implicit_hydrogens(f, true), 0);
+    }
+
+    #[test]
+    fn materialises_fillers() {
+        let three = fluorinated(3);
+        let saturated = three.with_implicit_hydrogens(false);
+        assert_eq!(saturated.atoms().node_count(), 5);
+        assert_eq!(saturated.fillers.len(), 1);
+        // fillers aren't neighbours, so nothing else changes
+        assert_eq!(saturated.neighbors(saturated.head).count(), 3);
+        assert_eq!(saturated.total_implicit_hydrogens(false), 0);
+        assert_eq!(three.with_implicit_hydrogens(true).fillers.len(), 0);
+    }
+
+    #[test]
+    fn isomorphism_lines_up_the_heads() {
+        let on = Atoms::from_smiles("ON").unwrap();
+        let no = Atoms::from_smiles("NO").unwrap();
+        assert!(on.is_isomorphic(&on.clone()));
+        assert!(!on.is_isomorphic(&no));
+        // the same atoms, numbered the other way round
+        let mut reversed = Atoms::new();
+        let n = reversed.add_node(Molecule::E(Element::N));
+        let o = reversed.add_node(Molecule::E(Element::O));
+        reversed.add_edge(o, n);
+        reversed.head = o;
+        assert!(on.is_isomorphic(&reversed));
+    }
+
+    #[test]
+    fn isomorphism_ignores_fillers() {
+        let three = fluorinated(3);
+        assert!(three.is_isomorphic(&three.with_implicit_hydrogens(false)));
+        assert!(!three.is_isomorphic(&fluorinated(2)));
+    }
+
+    // the molecule an origin points back to
+    fn resolve<'a>(atoms: &'a Atoms, origin: &Origin) -> &'a Molecule {
+        let group = origin.path.iter().fold(atoms, |group, idx| {
+            let Molecule::F(fg) = &group.atoms()[*idx] else {
+                panic!("origin paths go through functional groups");
+            };
+            fg.get_atoms()
+        });
+        &group.atoms()[origin.idx]
+    }
+
+    #[test]
+    fn expands_nested_groups_with_their_origins() {
+        let mut alk = Alkane::new();
+        alk.add_functional_group(FunctionalGroup::from(Value::from(3)));
+        alk.add_functional_group(FunctionalGroup::from(Value::Pair(
+            Box::new(Value::from(4)),
+            Box::new(Value::from(5)),
+        )));
+        let atoms = alk.get_atoms();
+        let expansion = atoms.expand();
+        let expanded = expansion.atoms.atoms();
+        // C, the ether's O and Li, and the amine's N with two more ethers
+        assert_eq!(expanded.node_count(), 8);
+        assert_eq!(expanded.edge_count(), 7);
+        assert_eq!(expansion.origins.len(), 8);
+        for (idx, origin) in &expansion.origins {
+            assert_eq!(resolve(atoms, origin), &expanded[*idx]);
+        }
+        let head = &expansion.origins[&expansion.atoms.head];
+        assert_eq!((head.path.as_slice(), head.idx), (&[][..], atoms.head));
+        let depths: Vec<usize> = [Element::Li, Element::N, Element::Be]
+            .iter()
+            .map(|e| {
+                let idx = expanded
+                    .node_indices()
+                    .find(|idx| expanded[*idx] == Molecule::E(e.clone()))
+                    .unwrap();
+                expansion.origins[&idx].path.len()
+            })
+            .collect();
+        assert_eq!(depths, [1, 1, 2]);
+        // bonds to a group become bonds to its head
+        let n = expanded
+            .node_indices()
+            .find(|idx| expanded[*idx] == Molecule::E(Element::N))
+            .unwrap();
+        assert!(expanded.find_edge(expansion.atoms.head, n).is_some());
+    }
+
+    #[test]
+    fn expansion_keeps_fillers() {
+        let saturated = fluorinated(3).with_implicit_hydrogens(false);
+        let expansion = saturated.expand();
+        assert_eq!(expansion.atoms.fillers.len(), 1);
+        assert_eq!(expansion.atoms.atoms().node_count(), 5);
+        assert!(expansion.atoms.is_isomorphic(&saturated));
+    }
+}
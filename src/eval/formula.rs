@@ -0,0 +1,139 @@
+// molecular formulas and molar masses of molecules
+
+use std::{collections::HashMap, fmt::Display};
+
+use super::{atom_like::AtomLike, atoms::Atoms, element::Element, molecule::Molecule};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Formula {
+    counts: HashMap<Element, usize>,
+}
+
+impl Formula {
+    // the formula of a molecule as it is written in a literal,
+    // with every nested group inlined and every implicit hydrogen counted,
+    // plus the leading `H` that the head bonds to.
+    pub fn of(atoms: &Atoms) -> Formula {
+        let expanded = atoms.flatten();
+        let mut counts: HashMap<Element, usize> = HashMap::new();
+        for idx in expanded.atoms().node_indices() {
+            if let Some(Molecule::E(e)) = expanded.atoms().node_weight(idx) {
+                *counts.entry(e.clone()).or_default() += 1;
+            }
+        }
+        if expanded.atoms().node_count() > 0 {
+            *counts.entry(Element::H).or_default() += expanded.total_implicit_hydrogens(true) + 1;
+        }
+        Formula { counts }
+    }
+
+    pub fn count(&self, e: &Element) -> usize {
+        self.counts.get(e).copied().unwrap_or_default()
+    }
+
+    // the elements in Hill order:
+    // carbon then hydrogen then everything else alphabetically if there is any carbon,
+    // otherwise everything alphabetically
+    pub fn elements(&self) -> Vec<(&Element, usize)> {
+        let has_carbon = self.counts.contains_key(&Element::C);
+        let rank = |e: &Element| match e {
+            Element::C if has_carbon => 0,
+            Element::H if has_carbon => 1,
+            _ => 2,
+        };
+        let mut elements: Vec<(&Element, usize)> =
+            self.counts.iter().map(|(e, n)| (e, *n)).collect();
+        elements.sort_by_cached_key(|(e, _)| (rank(e), e.to_string()));
+        elements
+    }
+
+    // the elements with no known mass, in Hill order
+    pub fn undiscovered(&self) -> Vec<&Element> {
+        self.elements()
+            .into_iter()
+            .filter(|(e, _)| e.info().is_none())
+            .map(|(e, _)| e)
+            .collect()
+    }
+
+    // the molar mass in g/mol, or `None` if there are undiscovered elements.
+    // antimatter weighs the same as its matter counterpart.
+    pub fn molar_mass(&self) -> Option<f64> {
+        self.counts
+            .iter()
+            .map(|(e, n)| e.info().map(|info| info.mass * *n as f64))
+            .sum()
+    }
+}
+
+impl Display for Formula {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (e, n) in self.elements() {
+            match n {
+                1 => write!(f, "{e}")?,
+                n => write!(f, "{e}{n}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Atoms {
+    pub fn formula(&self) -> Formula {
+        Formula::of(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::{
+        functional_groups::{ether::Ether, FgElement, FunctionalGroup},
+        number::Number,
+    };
+
+    fn formula(smiles: &str) -> Formula {
+        FunctionalGroup::from_smiles(smiles).unwrap().formula()
+    }
+
+    #[test]
+    fn counts_implicit_hydrogens() {
+        let ethane = formula("CC");
+        assert_eq!(ethane.count(&Element::C), 2);
+        assert_eq!(ethane.count(&Element::H), 6);
+        assert_eq!(ethane.to_string(), "C2H6");
+    }
+
+    #[test]
+    fn puts_carbon_and_hydrogen_first() {
+        assert_eq!(formula("C(O)(O[Li])[Na]").to_string(), "CH2LiNaO2");
+    }
+
+    #[test]
+    fn sorts_alphabetically_without_carbon() {
+        assert_eq!(formula("N(O[Li])O[Be]").to_string(), "BeH2LiNO2");
+        assert_eq!(formula("O").to_string(), "H2O");
+    }
+
+    #[test]
+    fn weighs_molecules() {
+        let mass = |smiles: &str| formula(smiles).molar_mass().unwrap();
+        assert!((mass("O") - 18.015).abs() < 1e-3);
+        assert!((mass("CC") - 30.070).abs() < 1e-3);
+        // antimatter weighs the same as matter
+        let anti = FunctionalGroup::Ether(Ether::new_with(FgElement::E(-Element::Fe)));
+        let iron = FunctionalGroup::Ether(Ether::new_with(FgElement::E(Element::Fe)));
+        let (anti, iron) = (anti.formula(), iron.formula());
+        assert_eq!(anti.count(&-Element::Fe), 1);
+        assert!((anti.molar_mass().unwrap() - iron.molar_mass().unwrap()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn undiscovered_elements_have_no_mass() {
+        let heavy = formula("O[Uue]");
+        assert_eq!(heavy.to_string(), "HOUue");
+        assert_eq!(heavy.molar_mass(), None);
+        assert_eq!(heavy.undiscovered(), [&Element::from(Number::from(119))]);
+        assert!(formula("O[Li]").undiscovered().is_empty());
+    }
+}
@@ -1,6 +1,8 @@
 pub mod alkane;
 pub mod amine;
 pub mod borinic;
+pub mod classify;
+pub mod ester;
 pub mod ether;
 pub mod fg_macros;
 pub mod sulfide;
@@ -8,16 +10,18 @@ pub mod sulfide;
 use std::fmt::Display;
 use std::ops::{Add, Sub};
 
-use self::alkane::Alkane;
+use self::alkane::{Alkane, Container};
 use self::sulfide::Sulfide;
-use self::{amine::Amine, borinic::BorinicAcid, ether::Ether};
+use self::{amine::Amine, borinic::BorinicAcid, ester::Ester, ether::Ether};
 
 use super::atom_like::AtomLike;
-use super::atoms::{AtomGraph, Atoms};
+use super::atoms::Atoms;
 use super::element::Element;
+use super::formula::Formula;
 use super::molecule::Molecule;
+use super::number::Number;
 use super::traits::{Valuable, Weighable};
-use super::value::Value;
+use super::value::{Value, ValueError};
 use petgraph::stable_graph::{EdgeIndex, NodeIndex};
 
 pub enum FgElement {
@@ -37,6 +41,7 @@ impl FgElement {
 #[derive(Debug, Clone)]
 pub enum FunctionalGroup {
     Ether(Ether),
+    Ester(Ester),
     BorinicAcid(BorinicAcid),
     Sulfide(Sulfide),
     Amine(Amine),
@@ -47,6 +52,9 @@ impl FunctionalGroup {
     pub fn new_ether(r: FgElement) -> Self {
         Self::Ether(Ether::new_with(r))
     }
+    pub fn new_ester(numerator: FgElement, denominator: FgElement) -> Self {
+        Self::Ester(Ester::new_with(numerator, denominator))
+    }
     pub fn new_borinic_acid(r: FgElement) -> Self {
         Self::BorinicAcid(BorinicAcid::new_with(r))
     }
@@ -59,22 +67,13 @@ impl FunctionalGroup {
     pub fn new_alkane() -> Self {
         Self::Alkane(Alkane::new())
     }
-
-    fn unwrap(&self) -> &AtomGraph {
-        match self {
-            Self::Ether(a) => a.atoms(),
-            Self::BorinicAcid(a) => a.atoms(),
-            Self::Sulfide(a) => a.atoms(),
-            Self::Amine(a) => a.atoms(),
-            Self::Alkane(a) => a.atoms(),
-        }
-    }
 }
 
 impl AtomLike for FunctionalGroup {
     fn get_atoms(&self) -> &Atoms {
         match self {
             Self::Ether(m) => m.get_atoms(),
+            Self::Ester(m) => m.get_atoms(),
             Self::BorinicAcid(m) => m.get_atoms(),
             Self::Sulfide(m) => m.get_atoms(),
             Self::Amine(m) => m.get_atoms(),
@@ -85,6 +84,7 @@ impl AtomLike for FunctionalGroup {
     fn get_atoms_mut(&mut self) -> &mut Atoms {
         match self {
             Self::Ether(m) => m.get_atoms_mut(),
+            Self::Ester(m) => m.get_atoms_mut(),
             Self::BorinicAcid(m) => m.get_atoms_mut(),
             Self::Sulfide(m) => m.get_atoms_mut(),
             Self::Amine(m) => m.get_atoms_mut(),
@@ -93,32 +93,27 @@ impl AtomLike for FunctionalGroup {
     }
 
     fn flatten(&self) -> Atoms {
-        match self {
-            Self::Ether(a) => a.0.flatten(),
-            Self::BorinicAcid(a) => a.flatten(),
-            Self::Sulfide(a) => a.0.flatten(),
-            Self::Amine(a) => a.flatten(),
-            Self::Alkane(a) => a.flatten(),
-        }
+        self.get_atoms().flatten()
     }
 
-    fn add_node(&mut self, _m: Molecule) -> NodeIndex {
-        todo!()
+    fn add_node(&mut self, m: Molecule) -> NodeIndex {
+        self.get_atoms_mut().add_node(m)
     }
 
-    fn add_edge(&mut self, _m: NodeIndex, _n: NodeIndex) -> EdgeIndex {
-        todo!()
+    fn add_edge(&mut self, m: NodeIndex, n: NodeIndex) -> EdgeIndex {
+        self.get_atoms_mut().add_edge(m, n)
     }
 }
 
 impl Weighable for FunctionalGroup {
-    fn atomic_numbers(&self) -> i64 {
+    fn checked_atomic_numbers(&self) -> Result<Number, ValueError> {
         match self {
-            Self::Ether(eth) => eth.atomic_numbers(),
-            Self::BorinicAcid(bor) => bor.atomic_numbers(),
-            Self::Sulfide(sulf) => sulf.atomic_numbers(),
-            Self::Amine(am) => am.atomic_numbers(),
-            Self::Alkane(alk) => alk.atomic_numbers(),
+            Self::Ether(eth) => eth.checked_atomic_numbers(),
+            Self::Ester(est) => est.checked_atomic_numbers(),
+            Self::BorinicAcid(bor) => bor.checked_atomic_numbers(),
+            Self::Sulfide(sulf) => sulf.checked_atomic_numbers(),
+            Self::Amine(am) => am.checked_atomic_numbers(),
+            Self::Alkane(alk) => alk.checked_atomic_numbers(),
         }
     }
 }
@@ -127,6 +122,7 @@ impl Valuable for FunctionalGroup {
     fn value(&self) -> Value {
         match self {
             Self::Ether(eth) => eth.value(),
+            Self::Ester(est) => est.value(),
             Self::BorinicAcid(bor) => bor.value(),
             Self::Sulfide(sul) => sul.value(),
             Self::Amine(ami) => ami.value(),
@@ -135,17 +131,59 @@ impl Valuable for FunctionalGroup {
     }
 }
 
+impl FunctionalGroup {
+    // whether both groups hold the same value, however they are spelled,
+    // so the ethers `OLi` and `OH_3` are equal, since both hold `3`
+    pub fn value_eq(&self, other: &FunctionalGroup) -> bool {
+        self.value() == other.value()
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            FunctionalGroup::Ether(_) => "Ether",
+            FunctionalGroup::Ester(_) => "Ester",
+            FunctionalGroup::BorinicAcid(_) => "BorinicAcid",
+            FunctionalGroup::Sulfide(_) => "Sulfide",
+            FunctionalGroup::Amine(_) => "Amine",
+            FunctionalGroup::Alkane(_) => "Alkane",
+        }
+    }
+
+    // the formula without the fluorines marking maps, which say how to read the carbons
+    // rather than being part of the data, so a map weighs the same as the list of its pairs
+    pub fn formula(&self) -> Formula {
+        self.unmarked().get_atoms().formula()
+    }
+
+    // a copy with the marker taken off every map, however deeply nested
+    fn unmarked(&self) -> FunctionalGroup {
+        let mut fg = self.clone();
+        if let FunctionalGroup::Alkane(alk) = &mut fg {
+            alk.set_container(Container::List);
+        }
+        for m in fg.get_atoms_mut().mut_atoms().node_weights_mut() {
+            if let Molecule::F(inner) = m {
+                *inner = inner.unmarked();
+            }
+        }
+        fg
+    }
+}
+
+// exact structural equality: the same kind of group with isomorphic atoms
+impl PartialEq for FunctionalGroup {
+    fn eq(&self, other: &Self) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+            && self.get_atoms().is_isomorphic(other.get_atoms())
+    }
+}
+
+impl Eq for FunctionalGroup {}
+
 impl Display for FunctionalGroup {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let p = match self {
-            FunctionalGroup::Ether(fg) => ("Ether", fg.value()),
-            FunctionalGroup::BorinicAcid(fg) => ("BorinicAcid", fg.value()),
-            FunctionalGroup::Sulfide(fg) => ("Sulfide", fg.value()),
-            FunctionalGroup::Amine(fg) => ("Amine", fg.value()),
-            FunctionalGroup::Alkane(fg) => ("Alkane", fg.value()),
-        };
-        let name = p.0;
-        let val = p.1;
+        let name = self.kind();
+        let val = self.value();
         f.write_fmt(format_args!("{name}({val})"))
     }
 }
@@ -156,6 +194,7 @@ impl Add for FunctionalGroup {
     fn add(self, rhs: Self) -> Self::Output {
         match (self, rhs) {
             (FunctionalGroup::Ether(l), FunctionalGroup::Ether(r)) => FunctionalGroup::Ether(l + r),
+            (FunctionalGroup::Ester(l), FunctionalGroup::Ester(r)) => FunctionalGroup::Ester(l + r),
             (FunctionalGroup::BorinicAcid(l), FunctionalGroup::BorinicAcid(r)) => {
                 let l = l.value();
                 let r = r.value();
@@ -190,6 +229,7 @@ impl Sub for FunctionalGroup {
     fn sub(self, rhs: Self) -> Self::Output {
         match (self, rhs) {
             (FunctionalGroup::Ether(l), FunctionalGroup::Ether(r)) => FunctionalGroup::Ether(l + r),
+            (FunctionalGroup::Ester(l), FunctionalGroup::Ester(r)) => FunctionalGroup::Ester(l - r),
             (FunctionalGroup::BorinicAcid(l), FunctionalGroup::BorinicAcid(r)) => {
                 let l = l.value();
                 let r = r.value();
@@ -223,6 +263,7 @@ impl From<Value> for FunctionalGroup {
     fn from(value: Value) -> Self {
         match value {
             Value::Number(_) => FunctionalGroup::Ether(Ether::from(value)),
+            Value::Rational(_) => FunctionalGroup::Ester(Ester::from(value)),
             Value::Boolean(_) => FunctionalGroup::BorinicAcid(BorinicAcid::from(value)),
             Value::String(_) => FunctionalGroup::Sulfide(Sulfide::from(value)),
             Value::Pair(_, _) => FunctionalGroup::Amine(Amine::from(value)),
@@ -230,3 +271,39 @@ impl From<Value> for FunctionalGroup {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // an oxygen bonded to `n` hydrogens, holding `n`
+    fn hydrogenated(n: usize) -> FunctionalGroup {
+        let mut ether = Ether::new();
+        let head = ether.head;
+        for _ in 0..n {
+            let h = ether.add_node(Molecule::E(Element::H));
+            ether.add_edge(head, h);
+        }
+        FunctionalGroup::Ether(ether)
+    }
+
+    #[test]
+    fn equal_values_can_differ_in_structure() {
+        let on = FunctionalGroup::from_smiles("ON").unwrap();
+        let chain = hydrogenated(7);
+        assert_eq!(on.value(), Value::from(7));
+        assert!(on.value_eq(&chain));
+        assert_ne!(on, chain);
+        assert_eq!(on, FunctionalGroup::from(Value::from(7)));
+    }
+
+    #[test]
+    fn structure_includes_the_kind_of_group() {
+        let ether = FunctionalGroup::from(Value::from(0));
+        let alkane = FunctionalGroup::from(Value::List(vec![]));
+        assert_ne!(ether, alkane);
+        assert!(!ether.value_eq(&alkane));
+        assert_eq!(hydrogenated(3), hydrogenated(3));
+        assert_ne!(hydrogenated(3), hydrogenated(4));
+    }
+}
@@ -1,6 +1,8 @@
+use std::borrow::Cow;
 use std::collections::vec_deque::Iter;
 use std::collections::{HashMap, VecDeque};
 
+use itertools::Itertools;
 use petgraph::stable_graph::{EdgeIndex, NodeIndex};
 
 use crate::eval::atom_like::AtomLike;
@@ -19,11 +21,24 @@ pub enum AlkaneElement {
     F(FunctionalGroup),
 }
 
+// what an alkane holds.
+// a map has a fluorine bonded to its head carbon, a list doesn't.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Container {
+    List,
+    Map,
+}
+
 #[derive(Debug, Clone)]
 pub struct Alkane {
     chain: Atoms,
     pub current_atom: NodeIndex,
     backbone: VecDeque<NodeIndex>,
+    // the fluorine marking a map
+    marker: Option<NodeIndex>,
+    // what is bonded to each backbone carbon, other than the backbone and the marker, in the order it was bonded.
+    // removing atoms frees their indices for the next ones added, so index order isn't bond order.
+    bonded: HashMap<NodeIndex, Vec<NodeIndex>>,
 }
 
 impl Alkane {
@@ -36,9 +51,17 @@ impl Alkane {
             chain: atoms,
             current_atom,
             backbone,
+            marker: None,
+            bonded: HashMap::from([(current_atom, Vec::new())]),
         }
     }
 
+    pub fn new_map() -> Alkane {
+        let mut alk = Alkane::new();
+        alk.set_container(Container::Map);
+        alk
+    }
+
     pub fn new_n_alkane(n: usize) -> Alkane {
         let mut alk = Alkane::new();
         let old_head = alk.head;
@@ -56,6 +79,60 @@ impl Alkane {
         alk
     }
 
+    // an alkane around a chain that was built some other way, bonded in index order
+    pub fn from_parts(
+        chain: Atoms,
+        current_atom: NodeIndex,
+        backbone: VecDeque<NodeIndex>,
+        marker: Option<NodeIndex>,
+    ) -> Alkane {
+        let bonded = backbone
+            .iter()
+            .map(|carbon| {
+                let molecules = chain
+                    .neighbors(*carbon)
+                    .filter(|n| !backbone.contains(n) && marker != Some(*n))
+                    .sorted()
+                    .collect();
+                (*carbon, molecules)
+            })
+            .collect();
+        Alkane {
+            chain,
+            current_atom,
+            backbone,
+            marker,
+            bonded,
+        }
+    }
+
+    pub fn marker(&self) -> Option<NodeIndex> {
+        self.marker
+    }
+
+    pub fn container(&self) -> Container {
+        match self.marker {
+            Some(_) => Container::Map,
+            None => Container::List,
+        }
+    }
+
+    // bonds or removes the marker fluorine, without touching the carbons
+    pub fn set_container(&mut self, container: Container) {
+        match (container, self.marker) {
+            (Container::Map, None) => {
+                let f = self.chain.add_node(Molecule::E(Element::F));
+                self.chain.add_edge(self.head, f);
+                self.marker = Some(f);
+            }
+            (Container::List, Some(f)) => {
+                self.chain.mut_atoms().remove_node(f);
+                self.marker = None;
+            }
+            _ => {}
+        }
+    }
+
     pub fn add_carbon_after(&mut self) {
         let carbon = self.chain.mut_atoms().add_node(Molecule::E(Element::C));
         self.chain
@@ -64,17 +141,31 @@ impl Alkane {
         // get the index to add after
         let current_index = self.get_atom_index(self.current_atom);
         match current_index {
-            Some(idx) => self.backbone.insert(idx + 1, carbon),
+            Some(idx) => {
+                // move the next carbon, if any, onto the new one
+                if let Some(&next) = self.backbone.get(idx + 1) {
+                    let edge = self
+                        .chain
+                        .atoms()
+                        .find_edge(self.current_atom, next)
+                        .unwrap();
+                    self.chain.mut_atoms().remove_edge(edge);
+                    self.chain.add_edge(carbon, next);
+                }
+                self.backbone.insert(idx + 1, carbon)
+            }
             None => self.backbone.push_back(carbon), // can't find the current atom, add to the end
         }
+        self.bonded.insert(carbon, Vec::new());
     }
     pub fn add_carbon_before(&mut self) {
         let carbon = self.chain.mut_atoms().add_node(Molecule::E(Element::C));
+        self.bonded.insert(carbon, Vec::new());
 
         let previous_index = self.get_current_atom_index();
         match previous_index {
             Some(idx) if idx > 0 => {
-                let prev_node = self.backbone.get(idx - 1).unwrap().clone();
+                let prev_node = self.backbone[idx - 1];
                 // detach previous and current
                 let prev_curr_edge = self
                     .chain
@@ -88,10 +179,16 @@ impl Alkane {
                 self.backbone.insert(idx, carbon);
             }
             Some(_idx) => {
-                // current atom is the first alkane
-                // attach carbon to this
-                self.chain.add_edge(self.current_atom, carbon);
-                self.backbone.push_back(carbon);
+                // current atom is the first carbon, so the new one becomes the head,
+                // taking a map's marker with it
+                self.chain.add_edge(carbon, self.current_atom);
+                self.backbone.push_front(carbon);
+                if let Some(f) = self.marker {
+                    let edge = self.chain.atoms().find_edge(self.head, f).unwrap();
+                    self.chain.mut_atoms().remove_edge(edge);
+                    self.chain.add_edge(carbon, f);
+                }
+                self.head = carbon;
             }
             None => panic!("shouldn't have nothing before an atom"),
         }
@@ -108,20 +205,20 @@ impl Alkane {
     pub fn add_molecule(&mut self, m: Molecule) {
         let m = self.chain.mut_atoms().add_node(m);
         self.chain.add_edge(self.current_atom, m);
+        self.bond(m);
     }
     pub fn add_element(&mut self, e: Element) {
-        let e = self.chain.mut_atoms().add_node(Molecule::E(e));
-        self.chain.add_edge(self.current_atom, e);
+        self.add_molecule(Molecule::E(e))
     }
     pub fn add_functional_group(&mut self, f: FunctionalGroup) {
-        let f = self.chain.mut_atoms().add_node(Molecule::F(f));
-        self.chain.add_edge(self.current_atom, f);
+        self.add_molecule(Molecule::F(f))
     }
     pub fn add_alkane(&mut self, alk: Alkane) {
-        let a = self
-            .chain
-            .add_node(Molecule::F(FunctionalGroup::Alkane(alk)));
-        self.chain.add_edge(self.current_atom, a);
+        self.add_molecule(Molecule::F(FunctionalGroup::Alkane(alk)))
+    }
+    // records an atom just bonded to the current carbon
+    fn bond(&mut self, idx: NodeIndex) {
+        self.bonded.entry(self.current_atom).or_default().push(idx);
     }
 
     pub fn move_up(&mut self) -> bool {
@@ -138,16 +235,17 @@ impl Alkane {
     pub fn move_down(&mut self) -> bool {
         let current_index = self.get_current_atom_index();
         match current_index {
-            Some(idx) => {
-                self.current_atom = *self.backbone.get(idx + 1).unwrap_or(&NodeIndex::default());
+            Some(idx) if idx + 1 < self.backbone.len() => {
+                self.current_atom = self.backbone[idx + 1];
                 true
             }
+            Some(_) => false,
             None => false,
         }
     }
 
     pub fn fill(&mut self, stuff: Vec<AlkaneElement>) {
-        let old_current = self.current_atom.clone();
+        let old_current = self.current_atom;
         // go to the head
         self.current_atom = *self.backbone.front().unwrap_or(&NodeIndex::default());
         // start attaching molecules
@@ -172,19 +270,61 @@ impl Alkane {
     pub fn get_currently_bonded_molecules(&self) -> Vec<&Molecule> {
         self.get_bonded_molecules(self.current_atom)
     }
+    // the molecules bonded to a carbon, in the order they were bonded
     pub fn get_bonded_molecules(&self, idx: NodeIndex) -> Vec<&Molecule> {
-        self.chain
-            .atoms()
-            .neighbors(idx)
-            .filter_map(|neighbor| {
-                self.chain
-                    .atoms()
-                    .node_weight(neighbor)
-                    .filter(|_| !self.backbone.contains(&neighbor))
+        self.bonded
+            .get(&idx)
+            .into_iter()
+            .flatten()
+            .map(|m| &self.chain.atoms()[*m])
+            .collect()
+    }
+    // unbonds the last `n` functional groups bonded to the current carbon,
+    // in the order they were bonded, or `None` if there aren't that many
+    pub fn take_bonded_groups(&mut self, n: usize) -> Option<Vec<FunctionalGroup>> {
+        let bonded = self.bonded.entry(self.current_atom).or_default();
+        let groups: Vec<usize> = (0..bonded.len())
+            .filter(|i| matches!(self.chain.atoms()[bonded[*i]], Molecule::F(_)))
+            .collect();
+        let start = groups.len().checked_sub(n)?;
+        // elements bonded between the groups stay where they are
+        let mut taken: Vec<NodeIndex> = groups[start..]
+            .iter()
+            .rev()
+            .map(|i| bonded.remove(*i))
+            .collect();
+        taken.reverse();
+        taken
+            .into_iter()
+            .map(|idx| match self.chain.mut_atoms().remove_node(idx) {
+                Some(Molecule::F(fg)) => Some(fg),
+                _ => None,
             })
             .collect()
     }
-    pub fn backbone(&self) -> Iter<NodeIndex> {
+    // the same alkane with its atoms added in the order they were bonded, if they weren't,
+    // for writing it out by index, like `from_parts` reads it back
+    pub fn in_bond_order(&self) -> Cow<'_, Alkane> {
+        if self
+            .bonded
+            .values()
+            .all(|m| m.windows(2).all(|w| w[0] < w[1]))
+        {
+            return Cow::Borrowed(self);
+        }
+        let mut alk = Alkane::new_n_alkane(self.backbone.len() - 1);
+        let backbone: Vec<NodeIndex> = alk.backbone().copied().collect();
+        for (carbon, old) in backbone.iter().zip(&self.backbone) {
+            alk.current_atom = *carbon;
+            for m in self.get_bonded_molecules(*old) {
+                alk.add_molecule(m.clone());
+            }
+        }
+        alk.current_atom = backbone[self.get_current_atom_index().unwrap_or_default()];
+        alk.set_container(self.container());
+        Cow::Owned(alk)
+    }
+    pub fn backbone(&self) -> Iter<'_, NodeIndex> {
         self.backbone.iter()
     }
 }
@@ -213,34 +353,30 @@ impl AtomLike for Alkane {
 
 impl Valuable for Alkane {
     fn value(&self) -> Value {
-        // 2 options:
-        // - a map if and only if all functional groups are pairs
-        // - a list otherwise
-
         // get the functional groups of all backbone atoms
         let backbone_bonds: Vec<Vec<&Molecule>> = self
             .backbone()
-            .map(|carbon| self.get_bonded_molecules(carbon.clone()))
+            .map(|carbon| self.get_bonded_molecules(*carbon))
+            .collect();
+        let vec: Vec<Value> = backbone_bonds
+            .iter()
+            .flat_map(|v| {
+                v.iter().map(|mol| {
+                    let Molecule::F(fg) = mol else {
+                        panic!("inner alkanes should not have elements, got {:?}", mol)
+                    };
+                    fg.value()
+                })
+            })
             .collect();
-        let vec: Vec<Value> = backbone_bonds.iter().map(|v| v.iter().map(|mol| {
-                let Molecule::F(fg) = mol else {panic!("inner alkanes should not have elements, got {:?}", mol)};
-                fg.value()
-            })).flatten().collect();
-
-        // if everything in the vec is a pair...
-        if vec.iter().all(|item| match item {
-            Value::Pair(_, _) => true,
-            _ => false,
-        }) {
-            // return hashmap instead
-            let mut map: HashMap<Value, Value> = HashMap::new();
-            vec.iter().for_each(|pair| {
-                let Value::Pair(k, v) = pair else {panic!("expected only pairs in vec of pairs, got non-pair {:?}", pair)};
-                map.insert(*k.clone(), *v.clone());
-            });
-            return Value::Map(map);
+
+        match self.container() {
+            Container::List => Value::List(vec),
+            // keeping the order of the carbons
+            Container::Map => Value::List(vec)
+                .into_map()
+                .unwrap_or_else(|err| panic!("maps should only hold pairs: {err}")),
         }
-        Value::List(vec)
     }
 }
 
@@ -255,13 +391,15 @@ impl From<Value> for Alkane {
                     .collect(),
             )
         } else if let Value::Map(m) = value {
-            Alkane::new_with(
+            let mut alk = Alkane::new_with(
                 m.iter()
                     .map(|(k, v)| {
                         AlkaneElement::F(FunctionalGroup::from(Value::from((k.clone(), v.clone()))))
                     })
                     .collect(),
-            )
+            );
+            alk.set_container(Container::Map);
+            alk
         } else {
             Alkane::new()
         }
@@ -269,3 +407,80 @@ impl From<Value> for Alkane {
 }
 
 fg_macros::ops!(Alkane);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(alk: &Alkane) -> Vec<Value> {
+        alk.get_currently_bonded_molecules()
+            .iter()
+            .map(|m| match m {
+                Molecule::F(fg) => fg.value(),
+                m => panic!("expected a functional group, got {m:?}"),
+            })
+            .collect()
+    }
+
+    fn bond(alk: &mut Alkane, n: i64) {
+        alk.add_functional_group(FunctionalGroup::from(Value::from(n)));
+    }
+
+    #[test]
+    fn takes_the_last_groups_bonded() {
+        let mut alk = Alkane::new();
+        (1..=4).for_each(|n| bond(&mut alk, n));
+        let taken: Vec<Value> = alk
+            .take_bonded_groups(2)
+            .unwrap()
+            .iter()
+            .map(|fg| fg.value())
+            .collect();
+        assert_eq!(taken, [Value::from(3), Value::from(4)]);
+        assert_eq!(values(&alk), [Value::from(1), Value::from(2)]);
+        assert!(alk.take_bonded_groups(3).is_none());
+    }
+
+    #[test]
+    fn groups_bonded_after_a_take_come_last() {
+        let mut alk = Alkane::new();
+        (1..=3).for_each(|n| bond(&mut alk, n));
+        alk.take_bonded_groups(2).unwrap();
+        // the new groups reuse the indices the taken ones left
+        (4..=6).for_each(|n| bond(&mut alk, n));
+        let bonded = [1, 4, 5, 6].map(Value::from);
+        assert_eq!(values(&alk), bonded);
+        assert_eq!(alk.value(), Value::List(bonded.to_vec()));
+
+        let written = alk.in_bond_order();
+        assert!(matches!(written, Cow::Owned(_)));
+        assert_eq!(values(&written), bonded);
+        assert!(matches!(written.in_bond_order(), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn takes_skip_elements() {
+        let mut alk = Alkane::new();
+        bond(&mut alk, 1);
+        alk.add_element(Element::Li);
+        bond(&mut alk, 2);
+        assert_eq!(alk.take_bonded_groups(2).unwrap().len(), 2);
+        assert_eq!(
+            alk.get_currently_bonded_molecules(),
+            [&Molecule::E(Element::Li)]
+        );
+    }
+
+    #[test]
+    fn maps_keep_the_order_of_their_carbons() {
+        let m = Value::Map(
+            [(3, 30), (1, 10), (2, 20)]
+                .map(|(k, v)| (Value::from(k), Value::from(v)))
+                .into_iter()
+                .collect(),
+        );
+        let fg = FunctionalGroup::from(m.clone());
+        assert_eq!(fg.value().to_string(), "{3:30,1:10,2:20}");
+        assert_eq!(fg.value(), m);
+    }
+}
@@ -36,7 +36,6 @@ impl Valuable for Amine {
         // the smaller-indexed molecule is the first of the pair
         // the larger-indexed molecule is the second of the pair
         let molecules: Vec<&Molecule> = self
-            .atoms()
             .neighbors(self.head)
             .map(|atom| self.atoms().node_weight(atom).unwrap())
             .collect();
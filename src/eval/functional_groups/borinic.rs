@@ -6,14 +6,16 @@ use crate::eval::{
 use super::{fg_macros, FgElement};
 
 #[derive(Debug, Clone)]
-pub struct BorinicAcid(Atoms);
+pub struct BorinicAcid(pub Atoms);
 
 impl BorinicAcid {
     pub fn new() -> BorinicAcid {
         let mut atoms = Atoms::new();
-        atoms.add_node(Molecule::E(Element::B));
-        atoms.add_node(Molecule::E(Element::O));
-        atoms.add_node(Molecule::E(Element::H));
+        let b = atoms.add_node(Molecule::E(Element::B));
+        let o = atoms.add_node(Molecule::E(Element::O));
+        let h = atoms.add_node(Molecule::E(Element::H));
+        atoms.add_edge(b, o);
+        atoms.add_edge(o, h);
         BorinicAcid(atoms)
     }
 
@@ -34,7 +36,7 @@ impl Valuable for BorinicAcid {
     fn value(&self) -> Value {
         // true if the head has more than 1 neighbor
         let atoms = &self;
-        Value::Boolean(atoms.atoms().neighbors(atoms.head).count() > 1)
+        Value::Boolean(atoms.neighbors(atoms.head).count() > 1)
     }
 }
 
@@ -0,0 +1,447 @@
+// recognising the functional group a molecule is shaped like, for molecules that weren't built
+// by the constructors here, like ones read from SMILES or bonded together by hand.
+// the head decides what to look for: `O` is an ether, `S` a sulfide, `N` an amine,
+// `C` an ester or an alkane and `B` a borinic acid, and everything bonded to them
+// is recognised the same way. groups that were already built are kept as they are.
+
+use std::{collections::HashSet, fmt::Display};
+
+use itertools::Itertools;
+use petgraph::stable_graph::NodeIndex;
+
+use crate::eval::{
+    atom_like::AtomLike,
+    atoms::{AtomGraph, Atoms},
+    element::Element,
+    molecule::Molecule,
+    number::Number,
+    value::ValueError,
+};
+
+use super::{
+    alkane::{Alkane, Container},
+    amine::Amine,
+    borinic::BorinicAcid,
+    ester::Ester,
+    ether::Ether,
+    sulfide::Sulfide,
+    FgElement, FunctionalGroup,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClassifyError {
+    Empty,
+    // atoms that aren't bonded to the rest of the molecule
+    Disconnected(String),
+    // a ring, which no functional group has
+    Ring(String),
+    // an atom that doesn't start any functional group
+    UnknownHead(String),
+    // a group with the wrong things bonded to it
+    Malformed {
+        kind: &'static str,
+        structure: String,
+    },
+    // a group whose value doesn't fit in a `Number`
+    Value(ValueError),
+}
+
+impl Display for ClassifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClassifyError::Empty => write!(f, "there are no atoms to recognise"),
+            ClassifyError::Disconnected(s) => write!(f, "`{s}` is more than one molecule"),
+            ClassifyError::Ring(s) => write!(f, "`{s}` has a ring, which no functional group has"),
+            ClassifyError::UnknownHead(s) => write!(
+                f,
+                "`{s}` does not start with `O`, `S`, `N`, `C` or `B`, so it is no functional group"
+            ),
+            ClassifyError::Malformed { kind, structure } => {
+                write!(f, "`{structure}` is not a well-formed {kind}")
+            }
+            ClassifyError::Value(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+// the group starting at `head` of any graph, made of everything bonded to it except `parent`,
+// so `classify(tape, group_head, Some(carbon))` is the group bonded to that carbon
+pub fn classify(
+    graph: &AtomGraph,
+    head: NodeIndex,
+    parent: Option<NodeIndex>,
+) -> Result<FunctionalGroup, ClassifyError> {
+    let atoms = Atoms::with_graph(graph.clone(), head);
+    Classifier { atoms: &atoms }.classify(head, parent)
+}
+
+// the whole molecule, starting at its head
+impl TryFrom<&Atoms> for FunctionalGroup {
+    type Error = ClassifyError;
+
+    fn try_from(atoms: &Atoms) -> Result<Self, Self::Error> {
+        Classifier { atoms }.classify(atoms.head, None)
+    }
+}
+
+struct Classifier<'a> {
+    atoms: &'a Atoms,
+}
+
+impl<'a> Classifier<'a> {
+    fn classify(
+        &self,
+        head: NodeIndex,
+        parent: Option<NodeIndex>,
+    ) -> Result<FunctionalGroup, ClassifyError> {
+        if !self.atoms.atoms().contains_node(head) {
+            return Err(ClassifyError::Empty);
+        }
+        self.check(head, parent)?;
+        self.group(head, parent)
+    }
+
+    // the group has to be a tree hanging off its head,
+    // and without a parent, it has to be the whole molecule
+    fn check(&self, head: NodeIndex, parent: Option<NodeIndex>) -> Result<(), ClassifyError> {
+        let mut visited = HashSet::new();
+        let mut stack = vec![(head, parent)];
+        while let Some((idx, from)) = stack.pop() {
+            if !visited.insert(idx) {
+                return Err(ClassifyError::Ring(self.structure(head, parent)));
+            }
+            stack.extend(
+                self.atoms
+                    .neighbors(idx)
+                    .filter(|n| Some(*n) != from)
+                    .map(|n| (n, Some(idx))),
+            );
+        }
+        let count = self
+            .atoms
+            .atoms()
+            .node_indices()
+            .filter(|idx| !self.atoms.is_filler(*idx))
+            .count();
+        if parent.is_none() && visited.len() < count {
+            return Err(ClassifyError::Disconnected(self.atoms.to_smiles()));
+        }
+        Ok(())
+    }
+
+    // the group starting at `idx`, made of everything bonded to it except `parent`
+    fn group(
+        &self,
+        idx: NodeIndex,
+        parent: Option<NodeIndex>,
+    ) -> Result<FunctionalGroup, ClassifyError> {
+        let e = match self.atoms.atoms().node_weight(idx).unwrap() {
+            Molecule::E(e) => e,
+            Molecule::F(fg) => {
+                if !self.children(idx, parent).is_empty() {
+                    return Err(self.malformed(fg.kind(), idx, parent));
+                }
+                return Ok(fg.clone());
+            }
+        };
+        match e {
+            Element::O => self.ether(idx, parent),
+            Element::S => self.sulfide(idx, parent),
+            Element::N => self.amine(idx, parent),
+            Element::C => match self.ester(idx, parent)? {
+                Some(ester) => Ok(ester),
+                None => self.alkane(idx, parent),
+            },
+            Element::B => self.borinic_acid(idx, parent),
+            _ => Err(ClassifyError::UnknownHead(self.structure(idx, parent))),
+        }
+    }
+
+    // an oxygen with at most one element bonded to it, whose atomic number is the value.
+    // hydrogens on either are only filling their valence, so they don't count.
+    fn ether(
+        &self,
+        idx: NodeIndex,
+        parent: Option<NodeIndex>,
+    ) -> Result<FunctionalGroup, ClassifyError> {
+        match self.children(idx, parent)[..] {
+            [] => {}
+            [child]
+                if matches!(self.atoms.atoms()[child], Molecule::E(_))
+                    && self.children(child, Some(idx)).is_empty() => {}
+            _ => return Err(self.malformed("Ether", idx, parent)),
+        }
+        Ok(FunctionalGroup::Ether(Ether(
+            self.atoms.branch(idx, parent).0,
+        )))
+    }
+
+    fn sulfide(
+        &self,
+        idx: NodeIndex,
+        parent: Option<NodeIndex>,
+    ) -> Result<FunctionalGroup, ClassifyError> {
+        let mut sulfide = Sulfide::new();
+        match self.children(idx, parent).as_slice() {
+            [] => {}
+            [child] => {
+                // the characters are either a single ether or an alkane of them, one per carbon
+                let fg = self.group(*child, Some(idx))?;
+                let is_text = match &fg {
+                    FunctionalGroup::Ether(_) => true,
+                    FunctionalGroup::Alkane(alk) => {
+                        alk.container() == Container::List
+                            && alk.backbone().all(|carbon| {
+                                matches!(
+                                    alk.get_bonded_molecules(*carbon)[..],
+                                    [] | [Molecule::F(FunctionalGroup::Ether(_))]
+                                )
+                            })
+                    }
+                    _ => false,
+                };
+                if !is_text {
+                    return Err(self.malformed("Sulfide", idx, parent));
+                }
+                let head = sulfide.head;
+                let fg = sulfide.add_node(Molecule::F(fg));
+                sulfide.add_edge(head, fg);
+            }
+            _ => return Err(self.malformed("Sulfide", idx, parent)),
+        }
+        Ok(FunctionalGroup::Sulfide(sulfide))
+    }
+
+    fn amine(
+        &self,
+        idx: NodeIndex,
+        parent: Option<NodeIndex>,
+    ) -> Result<FunctionalGroup, ClassifyError> {
+        // the key is whichever was bonded first
+        let [k, v] = self.children(idx, parent)[..] else {
+            return Err(self.malformed("Amine", idx, parent));
+        };
+        let k = FgElement::F(self.group(k, Some(idx))?);
+        let v = FgElement::F(self.group(v, Some(idx))?);
+        Ok(FunctionalGroup::Amine(Amine::new_with(k, v)))
+    }
+
+    // a carbon with no other carbons, a bare carbonyl oxygen,
+    // and an alkoxy oxygen with the denominator after it is an ester.
+    // everything else bonded to the carbon is the numerator.
+    fn ester(
+        &self,
+        idx: NodeIndex,
+        parent: Option<NodeIndex>,
+    ) -> Result<Option<FunctionalGroup>, ClassifyError> {
+        let neighbors: Vec<NodeIndex> = self
+            .atoms
+            .neighbors(idx)
+            .filter(|n| Some(*n) != parent)
+            .sorted()
+            .collect();
+        if neighbors.iter().any(|n| self.is(*n, Element::C)) {
+            return Ok(None);
+        }
+        let oxygens = neighbors.iter().filter(|n| self.is(**n, Element::O));
+        let carbonyl = oxygens.clone().find(|o| self.is_leaf(**o));
+        let alkoxy = oxygens.clone().find(|o| !self.is_leaf(**o));
+        let (Some(carbonyl), Some(alkoxy)) = (carbonyl, alkoxy) else {
+            return Ok(None);
+        };
+        let (atoms, copies) = self.atoms.branch(idx, parent);
+        let ester = Ester::from_parts(atoms, copies[carbonyl], copies[alkoxy]);
+        match ester.denominator() {
+            Ok(d) if d != Number::default() => {}
+            _ => return Err(self.malformed("Ester", idx, parent)),
+        }
+        // the numerator, or the fraction as a whole, can still overflow
+        ester.checked_value().map_err(ClassifyError::Value)?;
+        Ok(Some(FunctionalGroup::Ester(ester)))
+    }
+
+    fn alkane(
+        &self,
+        idx: NodeIndex,
+        parent: Option<NodeIndex>,
+    ) -> Result<FunctionalGroup, ClassifyError> {
+        let mut alk = Alkane::new();
+        let head = alk.current_atom;
+        let mut is_map = false;
+        let (mut carbon, mut previous) = (idx, parent);
+        loop {
+            let mut children = self.children(carbon, previous);
+            // a lone fluorine on the head carbon marks a map
+            if carbon == idx {
+                if let Some(marker) = children
+                    .iter()
+                    .position(|c| self.is(*c, Element::F) && self.is_leaf(*c))
+                {
+                    children.remove(marker);
+                    is_map = true;
+                }
+            }
+            // where there is a choice, the chain continues through the carbon bonded last,
+            // like the main chain of SMILES continuing after its branches
+            let next = children
+                .iter()
+                .rposition(|c| self.is(*c, Element::C))
+                .map(|i| children.remove(i));
+            for child in children {
+                alk.add_functional_group(self.group(child, Some(carbon))?);
+            }
+            let Some(next) = next else {
+                break;
+            };
+            alk.add_carbon_after();
+            alk.move_down();
+            (carbon, previous) = (next, Some(carbon));
+        }
+        alk.current_atom = head;
+        if is_map {
+            let all_pairs = alk.backbone().all(|carbon| {
+                alk.get_bonded_molecules(*carbon)
+                    .iter()
+                    .all(|m| matches!(m, Molecule::F(FunctionalGroup::Amine(_))))
+            });
+            if !all_pairs {
+                return Err(self.malformed("map", idx, parent));
+            }
+            alk.set_container(Container::Map);
+        }
+        Ok(FunctionalGroup::Alkane(alk))
+    }
+
+    fn borinic_acid(
+        &self,
+        idx: NodeIndex,
+        parent: Option<NodeIndex>,
+    ) -> Result<FunctionalGroup, ClassifyError> {
+        let mut neighbors: Vec<NodeIndex> = self
+            .atoms
+            .neighbors(idx)
+            .filter(|n| Some(*n) != parent)
+            .collect();
+        // the hydroxyl is an oxygen with at most a hydrogen after it
+        let hydroxyl = neighbors
+            .iter()
+            .position(|n| self.is(*n, Element::O) && self.children(*n, Some(idx)).is_empty());
+        let Some(hydroxyl) = hydroxyl else {
+            return Err(self.malformed("BorinicAcid", idx, parent));
+        };
+        neighbors.remove(hydroxyl);
+        // true if anything else is bonded to the boron, like `BorinicAcid::value`
+        match neighbors.len() {
+            0 => Ok(FunctionalGroup::BorinicAcid(BorinicAcid::from(false))),
+            1 => Ok(FunctionalGroup::BorinicAcid(BorinicAcid::from(true))),
+            _ => Err(self.malformed("BorinicAcid", idx, parent)),
+        }
+    }
+
+    fn is(&self, idx: NodeIndex, e: Element) -> bool {
+        self.atoms.atoms().node_weight(idx) == Some(&Molecule::E(e))
+    }
+
+    fn is_leaf(&self, idx: NodeIndex) -> bool {
+        self.atoms.neighbors(idx).count() == 1
+    }
+
+    // the atoms bonded to `idx` other than `parent`, in the order they were bonded.
+    // lone hydrogens are only ever hydrogens, so they are skipped.
+    fn children(&self, idx: NodeIndex, parent: Option<NodeIndex>) -> Vec<NodeIndex> {
+        self.atoms
+            .neighbors(idx)
+            .filter(|n| Some(*n) != parent)
+            .filter(|n| !(self.is(*n, Element::H) && self.is_leaf(*n)))
+            .sorted()
+            .collect()
+    }
+
+    // the branch as SMILES, to name it in errors
+    fn structure(&self, idx: NodeIndex, parent: Option<NodeIndex>) -> String {
+        self.atoms.branch(idx, parent).0.to_smiles()
+    }
+
+    fn malformed(
+        &self,
+        kind: &'static str,
+        idx: NodeIndex,
+        parent: Option<NodeIndex>,
+    ) -> ClassifyError {
+        ClassifyError::Malformed {
+            kind,
+            structure: self.structure(idx, parent),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::{traits::Valuable, value::Value};
+
+    fn classify(smiles: &str) -> Result<FunctionalGroup, ClassifyError> {
+        FunctionalGroup::try_from(&Atoms::from_smiles(smiles).unwrap())
+    }
+
+    #[test]
+    fn ethers_hold_one_element() {
+        assert_eq!(classify("O").unwrap().value(), Value::from(0));
+        assert_eq!(classify("O[Li]").unwrap().value(), Value::from(3));
+    }
+
+    #[test]
+    fn ethers_reject_longer_branches() {
+        for smiles in ["O[Li][Be]", "O([Li])[Be]", "OCC"] {
+            assert!(
+                matches!(
+                    classify(smiles),
+                    Err(ClassifyError::Malformed { kind: "Ether", .. })
+                ),
+                "{smiles}"
+            );
+        }
+    }
+
+    #[test]
+    fn lists_of_pairs_stay_lists() {
+        let pair = Value::Pair(Box::new(Value::from(3)), Box::new(Value::from(4)));
+        assert_eq!(
+            classify("CN(O[Li])O[Be]").unwrap().value(),
+            Value::List(vec![pair.clone()])
+        );
+        assert_eq!(
+            classify("C(F)N(O[Li])O[Be]").unwrap().value(),
+            Value::Map([(Value::from(3), Value::from(4))].into_iter().collect())
+        );
+        // a list of pairs built from values keeps its shape too
+        let list = FunctionalGroup::from(Value::List(vec![pair]));
+        assert_eq!(classify(&list.get_atoms().to_smiles()).unwrap(), list);
+    }
+
+    #[test]
+    fn empty_lists_and_maps_differ() {
+        let list = classify("C").unwrap();
+        let map = classify("CF").unwrap();
+        assert_eq!(list.value(), Value::List(vec![]));
+        assert_eq!(map.value(), Value::Map(Default::default()));
+        assert_ne!(list, map);
+        assert_eq!(FunctionalGroup::from(Value::Map(Default::default())), map);
+    }
+
+    #[test]
+    fn maps_weigh_like_their_pairs() {
+        let map = classify("C(F)N(O[Li])O[Be]").unwrap().formula();
+        assert_eq!(map, classify("CN(O[Li])O[Be]").unwrap().formula());
+        assert_eq!(map.to_string(), "CH4BeLiNO2");
+        assert_eq!(map.count(&Element::F), 0);
+    }
+
+    #[test]
+    fn unknown_heads_are_errors() {
+        assert!(matches!(
+            classify("[Li]"),
+            Err(ClassifyError::UnknownHead(_))
+        ));
+    }
+}
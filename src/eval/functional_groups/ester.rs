@@ -0,0 +1,142 @@
+use num_traits::CheckedDiv;
+use petgraph::stable_graph::NodeIndex;
+
+use crate::eval::{
+    atom_like::AtomLike,
+    atoms::Atoms,
+    element::Element,
+    molecule::Molecule,
+    number::{self, Number, Rational},
+    traits::Valuable,
+    value::{Value, ValueError},
+};
+
+use super::{fg_macros, FgElement};
+
+// `R'C(O)OR''`: the atomic numbers bonded to the carbon are the numerator,
+// and the atomic numbers bonded to the alkoxy oxygen are the denominator.
+#[derive(Debug, Clone)]
+pub struct Ester {
+    atoms: Atoms,
+    carbonyl: NodeIndex,
+    alkoxy: NodeIndex,
+}
+
+impl Ester {
+    // a carboxylic acid, `C(O)OH`, which is the ester 0/1
+    pub fn new() -> Ester {
+        let mut ester = Ester::skeleton();
+        let h = ester.add_node(Molecule::E(Element::H));
+        let alkoxy = ester.alkoxy;
+        ester.add_edge(alkoxy, h);
+        ester
+    }
+
+    pub fn new_with(numerator: FgElement, denominator: FgElement) -> Ester {
+        let mut ester = Ester::skeleton();
+        let (head, alkoxy) = (ester.head, ester.alkoxy);
+        let n = ester.add_node(numerator.as_molecule());
+        ester.add_edge(head, n);
+        let d = ester.add_node(denominator.as_molecule());
+        ester.add_edge(alkoxy, d);
+        ester
+    }
+
+    // an ester around atoms that are already shaped like one
+    pub fn from_parts(atoms: Atoms, carbonyl: NodeIndex, alkoxy: NodeIndex) -> Ester {
+        Ester {
+            atoms,
+            carbonyl,
+            alkoxy,
+        }
+    }
+
+    pub fn carbonyl(&self) -> NodeIndex {
+        self.carbonyl
+    }
+
+    pub fn alkoxy(&self) -> NodeIndex {
+        self.alkoxy
+    }
+
+    fn skeleton() -> Ester {
+        let mut atoms = Atoms::new();
+        let c = atoms.add_node(Molecule::E(Element::C));
+        let carbonyl = atoms.add_node(Molecule::E(Element::O));
+        let alkoxy = atoms.add_node(Molecule::E(Element::O));
+        atoms.add_edge(c, carbonyl);
+        atoms.add_edge(c, alkoxy);
+        Ester {
+            atoms,
+            carbonyl,
+            alkoxy,
+        }
+    }
+
+    pub fn numerator(&self) -> Result<Number, ValueError> {
+        let (carbonyl, alkoxy) = (self.carbonyl, self.alkoxy);
+        self.weigh_neighbors(self.head, |n| n != carbonyl && n != alkoxy)
+    }
+
+    pub fn denominator(&self) -> Result<Number, ValueError> {
+        let head = self.head;
+        self.weigh_neighbors(self.alkoxy, |n| n != head)
+    }
+
+    fn weigh_neighbors(
+        &self,
+        idx: NodeIndex,
+        keep: impl Fn(NodeIndex) -> bool,
+    ) -> Result<Number, ValueError> {
+        number::checked_sum(self.neighbors(idx).filter(|n| keep(*n)).map(|n| {
+            self.atoms()
+                .node_weight(n)
+                .unwrap()
+                .checked_atomic_numbers()
+        }))
+    }
+}
+
+impl Valuable for Ester {
+    fn value(&self) -> Value {
+        self.checked_value().unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl Ester {
+    // the numerator over the denominator, or an error if either doesn't fit in a `Number`
+    // or the fraction can't be reduced within one
+    pub fn checked_value(&self) -> Result<Value, ValueError> {
+        let (n, d) = (self.numerator()?, self.denominator()?);
+        if d == Number::default() {
+            return Err(ValueError::DivisionByZero(format!("{n} / {d}")));
+        }
+        let (n, d) = (Rational::from_integer(n), Rational::from_integer(d));
+        n.checked_div(&d)
+            .map(Value::from)
+            .ok_or_else(|| ValueError::Overflow(format!("{n} / {d}")))
+    }
+}
+
+fg_macros::fg!(Ester, atoms);
+
+impl From<Rational> for Ester {
+    fn from(r: Rational) -> Self {
+        Ester::new_with(
+            FgElement::E(Element::from(r.numer())),
+            FgElement::E(Element::from(r.denom())),
+        )
+    }
+}
+
+impl From<Value> for Ester {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Rational(r) => Ester::from(r),
+            Value::Number(n) => Ester::from(Rational::from(n)),
+            _ => Ester::new(),
+        }
+    }
+}
+
+fg_macros::ops!(Ester);
@@ -3,7 +3,7 @@ use crate::eval::{
     value::Value,
 };
 
-use super::{fg_macros, FgElement, FunctionalGroup};
+use super::{fg_macros, FgElement};
 
 #[derive(Debug, Clone)]
 pub struct Ether(pub Atoms);
@@ -35,12 +35,7 @@ fg_macros::fg!(Ether);
 
 impl From<char> for Ether {
     fn from(value: char) -> Ether {
-        let mut ether = Ether::new();
-        let e = Element::from(value as u32);
-        let e = ether.add_node(Molecule::E(e));
-        let head = ether.head;
-        ether.add_edge(head, e);
-        ether
+        Ether::from(value as u32)
     }
 }
 
@@ -58,6 +53,10 @@ macro_rules! impl_from_for_ether {
     ($t:ty) => {
         impl From<$t> for Ether {
             fn from(value: $t) -> Ether {
+                // a bare oxygen holds 0, and element 0 would be written like nitrogen
+                if value == <$t>::default() {
+                    return Ether::new();
+                }
                 let mut ether = Ether::new();
                 let e = Element::from(value);
                 let e = ether.add_node(Molecule::E(e));
@@ -78,5 +77,7 @@ impl_from_for_ether!(i16);
 impl_from_for_ether!(i32);
 impl_from_for_ether!(i64);
 impl_from_for_ether!(i128);
+#[cfg(feature = "bigint")]
+impl_from_for_ether!(crate::eval::number::Number);
 
 fg_macros::ops!(Ether);
@@ -18,11 +18,15 @@ macro_rules! fg {
         }
 
         impl Weighable for $T {
-            fn atomic_numbers(&self) -> i64 {
-                self.atoms()
-                    .neighbors(self.head)
-                    .map(|neighbor| self.atoms().node_weight(neighbor).unwrap().atomic_numbers())
-                    .sum()
+            fn checked_atomic_numbers(
+                &self,
+            ) -> Result<crate::eval::number::Number, crate::eval::value::ValueError> {
+                crate::eval::number::checked_sum(self.neighbors(self.head).map(|neighbor| {
+                    self.atoms()
+                        .node_weight(neighbor)
+                        .unwrap()
+                        .checked_atomic_numbers()
+                }))
             }
         }
 
@@ -51,11 +55,15 @@ macro_rules! fg {
         }
 
         impl Weighable for $T {
-            fn atomic_numbers(&self) -> i64 {
-                self.atoms()
-                    .neighbors(self.head)
-                    .map(|neighbor| self.atoms().node_weight(neighbor).unwrap().atomic_numbers())
-                    .sum()
+            fn checked_atomic_numbers(
+                &self,
+            ) -> Result<crate::eval::number::Number, crate::eval::value::ValueError> {
+                crate::eval::number::checked_sum(self.neighbors(self.head).map(|neighbor| {
+                    self.atoms()
+                        .node_weight(neighbor)
+                        .unwrap()
+                        .checked_atomic_numbers()
+                }))
             }
         }
 
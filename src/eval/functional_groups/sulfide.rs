@@ -1,6 +1,6 @@
 use crate::eval::{
-    atom_like::AtomLike, atoms::Atoms, element::Element, molecule::Molecule, traits::Valuable,
-    value::Value,
+    atom_like::AtomLike, atoms::Atoms, element::Element, molecule::Molecule, number,
+    traits::Valuable, value::Value,
 };
 
 use super::{
@@ -32,7 +32,6 @@ impl Valuable for Sulfide {
     fn value(&self) -> Value {
         let mut chars = Vec::new();
         self
-            .atoms()
             .neighbors(self.head)
             .map(|atom| self.atoms().node_weight(atom).unwrap())
             // sulfides should have either nothing, an ether, or an alkane
@@ -45,7 +44,7 @@ impl Valuable for Sulfide {
                         // iterate over the entire chain
                         let backbone_bonds: Vec<Vec<&Molecule>> = a
                             .backbone()
-                            .map(|carbon| a.get_bonded_molecules(carbon.clone()))
+                            .map(|carbon| a.get_bonded_molecules(*carbon))
                             .collect();
 
                         let mut cs: Vec<char> = backbone_bonds
@@ -57,7 +56,7 @@ impl Valuable for Sulfide {
                                 let Molecule::F(FunctionalGroup::Ether(e)) = m else {panic!("expected ethers on string alkane, instead got {:?}", m)};
                                 let v = e.value();
                                 let Value::Number(c) = v else {panic!("expected number from ether, got {:?}", v)};
-                                char::from_u32(c as u32).unwrap_or(char::REPLACEMENT_CHARACTER)
+                                number::to_char(&c)
                             })
                             .collect();
                         chars.append(&mut cs);
@@ -66,7 +65,7 @@ impl Valuable for Sulfide {
                         // ethers are just a single character
                         let v = e.value();
                         let Value::Number(c) = v else {panic!("expected number from ether, got {:?}", v)};
-                        chars.push(char::from_u32(c as u32).unwrap_or(char::REPLACEMENT_CHARACTER));
+                        chars.push(number::to_char(&c));
                     },
                     fg => panic!("expected nothing, ether, or alkane; got {:?}", fg)
                 }
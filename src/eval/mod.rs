@@ -1,7 +1,9 @@
 pub mod atom_like;
 pub mod atoms;
 pub mod element;
+pub mod formula;
 pub mod functional_groups;
 pub mod molecule;
+pub mod number;
 pub mod traits;
 pub mod value;
@@ -3,19 +3,22 @@
 //     pub head: NodeIndex,
 // }
 
-use super::{element::Element, functional_groups::FunctionalGroup, traits::Weighable};
+use super::{
+    element::Element, functional_groups::FunctionalGroup, number::Number, traits::Weighable,
+    value::ValueError,
+};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Molecule {
     E(Element),         // a singular element
     F(FunctionalGroup), // a singular functional group
 }
 
 impl Weighable for Molecule {
-    fn atomic_numbers(&self) -> i64 {
+    fn checked_atomic_numbers(&self) -> Result<Number, ValueError> {
         match self {
-            Molecule::E(e) => e.atomic_numbers(),
-            Molecule::F(fg) => fg.atomic_numbers(),
+            Molecule::E(e) => e.checked_atomic_numbers(),
+            Molecule::F(fg) => fg.checked_atomic_numbers(),
         }
     }
 }
@@ -0,0 +1,101 @@
+// the integer type of ether values.
+// `i64` by default, where arithmetic is checked instead of wrapping,
+// or arbitrary precision with the `bigint` feature.
+
+use num_rational::Ratio;
+
+use super::value::ValueError;
+
+#[cfg(feature = "bigint")]
+pub type Number = num_bigint::BigInt;
+#[cfg(not(feature = "bigint"))]
+pub type Number = i64;
+
+// the fractional type of ester values, always in lowest terms
+pub type Rational = Ratio<Number>;
+
+#[cfg(feature = "bigint")]
+pub fn checked_add(l: &Number, r: &Number) -> Option<Number> {
+    Some(l + r)
+}
+#[cfg(not(feature = "bigint"))]
+pub fn checked_add(l: &Number, r: &Number) -> Option<Number> {
+    l.checked_add(*r)
+}
+
+#[cfg(feature = "bigint")]
+pub fn checked_sub(l: &Number, r: &Number) -> Option<Number> {
+    Some(l - r)
+}
+#[cfg(not(feature = "bigint"))]
+pub fn checked_sub(l: &Number, r: &Number) -> Option<Number> {
+    l.checked_sub(*r)
+}
+
+#[cfg(feature = "bigint")]
+pub fn checked_neg(n: &Number) -> Option<Number> {
+    Some(-n)
+}
+#[cfg(not(feature = "bigint"))]
+pub fn checked_neg(n: &Number) -> Option<Number> {
+    n.checked_neg()
+}
+
+#[cfg(feature = "bigint")]
+pub fn checked_mul(l: &Number, r: &Number) -> Option<Number> {
+    Some(l * r)
+}
+#[cfg(not(feature = "bigint"))]
+pub fn checked_mul(l: &Number, r: &Number) -> Option<Number> {
+    l.checked_mul(*r)
+}
+
+#[cfg(feature = "bigint")]
+pub fn checked_from_usize(n: usize) -> Option<Number> {
+    Some(Number::from(n))
+}
+#[cfg(not(feature = "bigint"))]
+pub fn checked_from_usize(n: usize) -> Option<Number> {
+    Number::try_from(n).ok()
+}
+
+// the number as an `i64`, or `None` if it doesn't fit, for targets without arbitrary precision
+#[cfg(feature = "bigint")]
+pub fn to_i64(n: &Number) -> Option<i64> {
+    i64::try_from(n).ok()
+}
+#[cfg(not(feature = "bigint"))]
+pub fn to_i64(n: &Number) -> Option<i64> {
+    Some(*n)
+}
+
+// add up atomic numbers, erroring on the first one that doesn't fit
+pub fn checked_sum(
+    mut numbers: impl Iterator<Item = Result<Number, ValueError>>,
+) -> Result<Number, ValueError> {
+    numbers.try_fold(Number::default(), |sum, n| {
+        let n = n?;
+        checked_add(&sum, &n).ok_or_else(|| ValueError::Overflow(format!("{sum} + {n}")))
+    })
+}
+
+// parse a string of decimal digits, or `None` if it doesn't fit
+pub fn parse(digits: &str) -> Option<Number> {
+    digits.parse().ok()
+}
+
+// the unicode scalar value of a number, for strings
+#[cfg(feature = "bigint")]
+pub fn to_char(n: &Number) -> char {
+    u32::try_from(n)
+        .ok()
+        .and_then(char::from_u32)
+        .unwrap_or(char::REPLACEMENT_CHARACTER)
+}
+#[cfg(not(feature = "bigint"))]
+pub fn to_char(n: &Number) -> char {
+    u32::try_from(*n)
+        .ok()
+        .and_then(char::from_u32)
+        .unwrap_or(char::REPLACEMENT_CHARACTER)
+}
@@ -1,8 +1,17 @@
-use super::value::Value;
+use super::{
+    number::Number,
+    value::{Value, ValueError},
+};
 
 pub trait Valuable {
     fn value(&self) -> Value;
 }
 pub trait Weighable {
-    fn atomic_numbers(&self) -> i64;
+    // the sum of atomic numbers, or an error if it doesn't fit in a `Number`
+    fn checked_atomic_numbers(&self) -> Result<Number, ValueError>;
+
+    fn atomic_numbers(&self) -> Number {
+        self.checked_atomic_numbers()
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
 }
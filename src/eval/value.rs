@@ -1,49 +1,118 @@
 use std::{
-    collections::HashMap,
+    cmp::Ordering,
+    collections::{hash_map::DefaultHasher, HashMap},
     fmt::Display,
-    hash::Hash,
-    ops::{Add, Sub},
+    hash::{Hash, Hasher},
+    ops::{Add, Div, Sub},
 };
 
-#[derive(Debug, Clone)]
+use indexmap::IndexMap;
+use num_traits::{CheckedAdd, CheckedDiv, CheckedSub};
+
+use super::number::{self, Number, Rational};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Value {
-    Number(i64),                  // ether
+    Number(Number),               // ether
+    Rational(Rational),           // ester, never a whole number
     Boolean(bool),                // borinic acid
     String(Vec<char>),            // sulfide
     Pair(Box<Value>, Box<Value>), // amine
     List(Vec<Value>),             // alkane
-    Map(HashMap<Value, Value>),   // alkane
+    Map(IndexMap<Value, Value>),  // alkane, in the order of its carbons
 }
 
-impl PartialEq for Value {
-    fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (Self::Number(l0), Self::Number(r0)) => l0 == r0,
-            (Self::Boolean(l0), Self::Boolean(r0)) => l0 == r0,
-            (Self::String(l0), Self::String(r0)) => l0 == r0,
-            (Self::Pair(l0, l1), Self::Pair(r0, r1)) => **l0 == **r0 && **l1 == **r1,
-            (Self::List(l0), Self::List(r0)) => {
-                l0.len() == r0.len() && l0.iter().zip(r0.iter()).all(|t| t.0 == t.1)
+impl Value {
+    // the position of this value's type in the ordering of types
+    fn type_rank(&self) -> u8 {
+        match self {
+            Value::Number(_) => 0,
+            Value::Rational(_) => 1,
+            Value::Boolean(_) => 2,
+            Value::String(_) => 3,
+            Value::Pair(_, _) => 4,
+            Value::List(_) => 5,
+            Value::Map(_) => 6,
+        }
+    }
+
+    // numbers and rationals as a rational, for arithmetic between the two
+    fn as_rational(&self) -> Option<Rational> {
+        match self {
+            Value::Number(n) => Some(Rational::from(n.to_owned())),
+            Value::Rational(r) => Some(r.to_owned()),
+            _ => None,
+        }
+    }
+
+    // the entries of a map, sorted by key, so that maps compare independently of their layout
+    fn sorted_entries(m: &IndexMap<Value, Value>) -> Vec<(&Value, &Value)> {
+        let mut entries: Vec<(&Value, &Value)> = m.iter().collect();
+        entries.sort();
+        entries
+    }
+}
+
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        core::mem::discriminant(self).hash(state);
+        match self {
+            Value::Number(n) => n.hash(state),
+            Value::Rational(r) => r.hash(state),
+            Value::Boolean(b) => b.hash(state),
+            Value::String(s) => s.hash(state),
+            Value::Pair(l, r) => {
+                l.hash(state);
+                r.hash(state);
             }
-            (Self::Map(l0), Self::Map(r0)) => {
-                l0.len() == r0.len()
-                    && l0.keys().map(|k| k.clone()).all(|k| {
-                        r0.keys()
-                            .map(|c| c.clone())
-                            .find(|e| k.clone() == e.clone())
-                            .is_some()
+            Value::List(v) => v.hash(state),
+            Value::Map(m) => {
+                // equal maps can have their entries in a different order,
+                // so the entries are combined with an order-independent sum
+                m.len().hash(state);
+                m.iter()
+                    .map(|entry| {
+                        let mut hasher = DefaultHasher::new();
+                        entry.hash(&mut hasher);
+                        hasher.finish()
                     })
+                    .fold(0u64, |sum, h| sum.wrapping_add(h))
+                    .hash(state);
             }
-            _ => false,
         }
     }
 }
 
-impl Eq for Value {}
+// numbers and rationals are ordered numerically between each other, so `1/2 < 5`.
+// a rational is never whole, so a number and a rational are never `Ordering::Equal`.
+// other values are ordered first by type
+// (numbers and rationals < booleans < strings < pairs < lists < maps),
+// then by contents: `false < true`, and strings, pairs and lists lexicographically.
+// maps compare as their entries sorted by key, so equal maps are always `Ordering::Equal`.
+impl Ord for Value {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Value::Number(l), Value::Number(r)) => l.cmp(r),
+            (Value::Rational(l), Value::Rational(r)) => l.cmp(r),
+            (
+                l @ (Value::Number(_) | Value::Rational(_)),
+                r @ (Value::Number(_) | Value::Rational(_)),
+            ) => l.as_rational().cmp(&r.as_rational()),
+            (Value::Boolean(l), Value::Boolean(r)) => l.cmp(r),
+            (Value::String(l), Value::String(r)) => l.cmp(r),
+            (Value::Pair(la, lb), Value::Pair(ra, rb)) => la.cmp(ra).then_with(|| lb.cmp(rb)),
+            (Value::List(l), Value::List(r)) => l.cmp(r),
+            (Value::Map(l), Value::Map(r)) => {
+                Value::sorted_entries(l).cmp(&Value::sorted_entries(r))
+            }
+            (l, r) => l.type_rank().cmp(&r.type_rank()),
+        }
+    }
+}
 
-impl Hash for Value {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        core::mem::discriminant(self).hash(state);
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
@@ -51,10 +120,9 @@ impl Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Value::Number(n) => f.write_fmt(format_args!("{}", n)),
+            Value::Rational(r) => f.write_fmt(format_args!("{}", r)),
             Value::Boolean(b) => f.write_fmt(format_args!("{}", b)),
-            Value::String(s) => {
-                f.write_fmt(format_args!("\"{}\"", s.into_iter().collect::<String>()))
-            }
+            Value::String(s) => f.write_fmt(format_args!("\"{}\"", s.iter().collect::<String>())),
             Value::Pair(l, r) => f.write_fmt(format_args!("({},{})", l, r)),
             Value::List(v) => f.write_fmt(format_args!(
                 "[{}]",
@@ -74,78 +142,236 @@ impl Display for Value {
     }
 }
 
-impl Add for Value {
-    type Output = Value;
-
-    fn add(self, rhs: Self) -> Self::Output {
+impl Value {
+    // add two values of the same type, erroring instead of overflowing
+    pub fn checked_add(self, rhs: Self) -> Result<Value, ValueError> {
         match (self, rhs) {
-            (Value::Number(l), Value::Number(r)) => Value::Number(l + r),
-            (Value::Boolean(l), Value::Boolean(r)) => Value::Boolean(l && r),
+            (Value::Number(l), Value::Number(r)) => number::checked_add(&l, &r)
+                .map(Value::Number)
+                .ok_or_else(|| ValueError::Overflow(format!("{l} + {r}"))),
+            (
+                l @ (Value::Number(_) | Value::Rational(_)),
+                r @ (Value::Number(_) | Value::Rational(_)),
+            ) => {
+                let (l, r) = (l.as_rational().unwrap(), r.as_rational().unwrap());
+                l.checked_add(&r)
+                    .map(Value::from)
+                    .ok_or_else(|| ValueError::Overflow(format!("{l} + {r}")))
+            }
+            (Value::Boolean(l), Value::Boolean(r)) => Ok(Value::Boolean(l && r)),
             (Value::String(l), Value::String(r)) => {
                 let mut v = l;
                 r.iter().for_each(|e| v.push(*e));
-                Value::String(v)
+                Ok(Value::String(v))
             }
             (Value::Pair(la, lb), Value::Pair(ra, rb)) => {
-                let a = *la + *ra;
-                let b = *lb + *rb;
-                Value::Pair(Box::new(a), Box::new(b))
+                let a = la.checked_add(*ra)?;
+                let b = lb.checked_add(*rb)?;
+                Ok(Value::Pair(Box::new(a), Box::new(b)))
             }
             (Value::List(l), Value::List(r)) => {
                 let mut v = l;
                 r.iter().for_each(|e| v.push(e.clone()));
-                Value::List(v)
+                Ok(Value::List(v))
             }
             (Value::Map(l), Value::Map(r)) => {
                 let mut m = l;
                 r.iter().for_each(|(k, v)| {
                     m.insert(k.clone(), v.clone());
                 });
-                Value::Map(m)
+                Ok(Value::Map(m))
             }
-            (l, r) => panic!("not supported for Add: {} and {}", l, r),
+            (l, r) => Err(ValueError::Unsupported {
+                op: "Add",
+                l: Box::new(l),
+                r: Box::new(r),
+            }),
         }
     }
-}
-impl Sub for Value {
-    type Output = Value;
 
-    fn sub(self, rhs: Self) -> Self::Output {
+    // subtract two values of the same type, erroring instead of overflowing
+    pub fn checked_sub(self, rhs: Self) -> Result<Value, ValueError> {
         match (self, rhs) {
-            (Value::Number(l), Value::Number(r)) => Value::Number(l - r),
-            (Value::Boolean(l), Value::Boolean(r)) => Value::Boolean(l || r),
+            (Value::Number(l), Value::Number(r)) => number::checked_sub(&l, &r)
+                .map(Value::Number)
+                .ok_or_else(|| ValueError::Overflow(format!("{l} - {r}"))),
+            (
+                l @ (Value::Number(_) | Value::Rational(_)),
+                r @ (Value::Number(_) | Value::Rational(_)),
+            ) => {
+                let (l, r) = (l.as_rational().unwrap(), r.as_rational().unwrap());
+                l.checked_sub(&r)
+                    .map(Value::from)
+                    .ok_or_else(|| ValueError::Overflow(format!("{l} - {r}")))
+            }
+            (Value::Boolean(l), Value::Boolean(r)) => Ok(Value::Boolean(l || r)),
             (Value::String(l), Value::String(r)) => {
                 let v = l;
-                Value::String(v.iter().filter(|c| r.contains(c)).map(|c| *c).collect())
+                Ok(Value::String(
+                    v.iter().filter(|c| r.contains(c)).copied().collect(),
+                ))
             }
             (Value::Pair(la, lb), Value::Pair(ra, rb)) => {
-                let a = *la - *ra;
-                let b = *lb - *rb;
-                Value::Pair(Box::new(a), Box::new(b))
+                let a = la.checked_sub(*ra)?;
+                let b = lb.checked_sub(*rb)?;
+                Ok(Value::Pair(Box::new(a), Box::new(b)))
             }
             (Value::List(l), Value::List(r)) => {
                 let v = l;
-                Value::List(
-                    v.iter()
-                        .filter(|c| r.contains(c))
-                        .map(|c| c.clone())
-                        .collect(),
-                )
+                Ok(Value::List(
+                    v.iter().filter(|c| r.contains(c)).cloned().collect(),
+                ))
             }
             (Value::Map(l), Value::Map(r)) => {
                 let m = l;
-                Value::Map(
+                Ok(Value::Map(
                     m.iter()
-                        .filter(|(k, _)| r.contains_key(k))
+                        .filter(|(k, _)| r.contains_key(*k))
                         .map(|(k, v)| (k.clone(), v.clone()))
                         .collect(),
-                )
+                ))
+            }
+            (l, r) => Err(ValueError::Unsupported {
+                op: "Sub",
+                l: Box::new(l),
+                r: Box::new(r),
+            }),
+        }
+    }
+}
+
+impl Value {
+    // divide two values of the same type.
+    // dividing integers is exact, so `1 Db 3` is the rational `1/3` and `4 Db 2` is `2`.
+    pub fn checked_div(self, rhs: Self) -> Result<Value, ValueError> {
+        match (self, rhs) {
+            (
+                l @ (Value::Number(_) | Value::Rational(_)),
+                r @ (Value::Number(_) | Value::Rational(_)),
+            ) => {
+                let (l, r) = (l.as_rational().unwrap(), r.as_rational().unwrap());
+                if r == Rational::default() {
+                    return Err(ValueError::DivisionByZero(format!("{l} / {r}")));
+                }
+                l.checked_div(&r)
+                    .map(Value::from)
+                    .ok_or_else(|| ValueError::Overflow(format!("{l} / {r}")))
+            }
+            (Value::Pair(la, lb), Value::Pair(ra, rb)) => {
+                let a = la.checked_div(*ra)?;
+                let b = lb.checked_div(*rb)?;
+                Ok(Value::Pair(Box::new(a), Box::new(b)))
+            }
+            (Value::Map(l), Value::Map(r)) => {
+                let m = l;
+                Ok(Value::Map(
+                    m.into_iter().filter(|(k, _)| r.contains_key(k)).collect(),
+                ))
             }
-            (l, r) => panic!("not supported for Sub: {} and {}", l, r),
+            (l, r) => Err(ValueError::Unsupported {
+                op: "Div",
+                l: Box::new(l),
+                r: Box::new(r),
+            }),
         }
     }
 }
 
+impl Value {
+    // a list of pairs as a map, keeping the order of the pairs.
+    // later pairs overwrite the values of earlier pairs with the same key.
+    pub fn into_map(self) -> Result<Value, ValueError> {
+        match self {
+            Value::Map(m) => Ok(Value::Map(m)),
+            Value::List(v) if v.iter().all(|e| matches!(e, Value::Pair(_, _))) => Ok(Value::Map(
+                v.into_iter()
+                    .map(|e| {
+                        let Value::Pair(k, v) = e else { unreachable!() };
+                        (*k, *v)
+                    })
+                    .collect(),
+            )),
+            value => Err(ValueError::Conversion {
+                to: "Map",
+                value: Box::new(value),
+            }),
+        }
+    }
+
+    // a map as a list of its pairs
+    pub fn into_list(self) -> Result<Value, ValueError> {
+        match self {
+            Value::List(v) => Ok(Value::List(v)),
+            Value::Map(m) => Ok(Value::List(
+                m.into_iter()
+                    .map(|(k, v)| Value::Pair(Box::new(k), Box::new(v)))
+                    .collect(),
+            )),
+            value => Err(ValueError::Conversion {
+                to: "List",
+                value: Box::new(value),
+            }),
+        }
+    }
+}
+
+impl Add for Value {
+    type Output = Value;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        self.checked_add(rhs).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+impl Sub for Value {
+    type Output = Value;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.checked_sub(rhs).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+impl Div for Value {
+    type Output = Value;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        self.checked_div(rhs).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueError {
+    // the result doesn't fit in a `Number`
+    Overflow(String),
+    DivisionByZero(String),
+    Unsupported {
+        op: &'static str,
+        l: Box<Value>,
+        r: Box<Value>,
+    },
+    Conversion {
+        to: &'static str,
+        value: Box<Value>,
+    },
+}
+
+impl Display for ValueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValueError::Overflow(expr) => {
+                write!(f, "integer overflow in {expr}")?;
+                #[cfg(not(feature = "bigint"))]
+                write!(f, "; enable the `bigint` feature for arbitrary precision")?;
+                Ok(())
+            }
+            ValueError::DivisionByZero(expr) => write!(f, "division by zero in {expr}"),
+            ValueError::Unsupported { op, l, r } => {
+                write!(f, "not supported for {op}: {l} and {r}")
+            }
+            ValueError::Conversion { to, value } => write!(f, "cannot convert {value} to a {to}"),
+        }
+    }
+}
+
+#[allow(dead_code)]
 #[derive(Clone)]
 enum ValueType {
     // Number
@@ -177,7 +403,23 @@ macro_rules! impl_from_num_for_value {
     ($T:ty) => {
         impl From<$T> for Value {
             fn from(value: $T) -> Self {
-                Value::Number(value as i64)
+                Value::Number(Number::from(value))
+            }
+        }
+    };
+}
+
+// without `bigint`, these don't always fit in a `Number`
+#[cfg(not(feature = "bigint"))]
+macro_rules! impl_try_from_num_for_value {
+    ($T:ty) => {
+        impl TryFrom<$T> for Value {
+            type Error = ValueError;
+
+            fn try_from(value: $T) -> Result<Self, Self::Error> {
+                Number::try_from(value)
+                    .map(Value::Number)
+                    .map_err(|_| ValueError::Overflow(format!("{value}")))
             }
         }
     };
@@ -186,13 +428,35 @@ macro_rules! impl_from_num_for_value {
 impl_from_num_for_value!(u8);
 impl_from_num_for_value!(u16);
 impl_from_num_for_value!(u32);
-impl_from_num_for_value!(u64);
-impl_from_num_for_value!(u128);
 impl_from_num_for_value!(i8);
 impl_from_num_for_value!(i16);
 impl_from_num_for_value!(i32);
 impl_from_num_for_value!(i64);
+#[cfg(feature = "bigint")]
+impl_from_num_for_value!(u64);
+#[cfg(feature = "bigint")]
+impl_from_num_for_value!(u128);
+#[cfg(feature = "bigint")]
 impl_from_num_for_value!(i128);
+#[cfg(feature = "bigint")]
+impl_from_num_for_value!(Number);
+#[cfg(not(feature = "bigint"))]
+impl_try_from_num_for_value!(u64);
+#[cfg(not(feature = "bigint"))]
+impl_try_from_num_for_value!(u128);
+#[cfg(not(feature = "bigint"))]
+impl_try_from_num_for_value!(i128);
+
+// whole rationals become plain numbers, so every value has one representation
+impl From<Rational> for Value {
+    fn from(value: Rational) -> Self {
+        if value.is_integer() {
+            Value::Number(value.to_integer())
+        } else {
+            Value::Rational(value)
+        }
+    }
+}
 
 impl From<bool> for Value {
     fn from(value: bool) -> Self {
@@ -262,13 +526,22 @@ impl From<ValueType> for Value {
             ValueType::U8(v) => Value::from(v),
             ValueType::U16(v) => Value::from(v),
             ValueType::U32(v) => Value::from(v),
+            #[cfg(feature = "bigint")]
             ValueType::U64(v) => Value::from(v),
+            #[cfg(not(feature = "bigint"))]
+            ValueType::U64(v) => Value::try_from(v).unwrap_or_else(|err| panic!("{err}")),
+            #[cfg(feature = "bigint")]
             ValueType::U128(v) => Value::from(v),
+            #[cfg(not(feature = "bigint"))]
+            ValueType::U128(v) => Value::try_from(v).unwrap_or_else(|err| panic!("{err}")),
             ValueType::I8(v) => Value::from(v),
             ValueType::I16(v) => Value::from(v),
             ValueType::I32(v) => Value::from(v),
             ValueType::I64(v) => Value::from(v),
+            #[cfg(feature = "bigint")]
             ValueType::I128(v) => Value::from(v),
+            #[cfg(not(feature = "bigint"))]
+            ValueType::I128(v) => Value::try_from(v).unwrap_or_else(|err| panic!("{err}")),
             ValueType::Bool(v) => Value::from(v),
             ValueType::String(v) => Value::from(v),
             ValueType::Str(v) => Value::from(v),
@@ -279,3 +552,118 @@ impl From<ValueType> for Value {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rational(n: i64, d: i64) -> Value {
+        Value::from(Rational::new(Number::from(n), Number::from(d)))
+    }
+
+    #[test]
+    fn only_suggests_bigint_without_it() {
+        let message = ValueError::Overflow(String::from("1 + 1")).to_string();
+        assert_eq!(
+            message.contains("enable the `bigint` feature"),
+            cfg!(not(feature = "bigint"))
+        );
+    }
+
+    #[test]
+    fn numbers_and_rationals_order_numerically() {
+        let half = rational(1, 2);
+        assert!(half < Value::from(5));
+        assert!(Value::from(0) < half);
+        assert!(Value::from(-1) < half);
+        assert!(rational(4, 3) > Value::from(1));
+        assert!(rational(2, 3) < Value::from(1));
+        assert_ne!(half.cmp(&Value::from(0)), Ordering::Equal);
+    }
+
+    #[test]
+    fn other_types_order_by_type_first() {
+        assert!(Value::from(1000) < Value::from(false));
+        assert!(rational(1, 3) < Value::from(false));
+        assert!(Value::from(true) < Value::from("a"));
+    }
+
+    #[test]
+    fn integer_division_is_exact() {
+        assert_eq!(
+            Value::from(4).checked_div(Value::from(2)),
+            Ok(Value::from(2))
+        );
+        assert!(matches!(
+            Value::from(1).checked_div(Value::from(3)),
+            Ok(Value::Rational(_))
+        ));
+        assert!(matches!(
+            Value::from(1).checked_div(Value::from(0)),
+            Err(ValueError::DivisionByZero(_))
+        ));
+    }
+
+    fn map(entries: &[(i64, &str)]) -> Value {
+        Value::Map(
+            entries
+                .iter()
+                .map(|(k, v)| (Value::from(*k), Value::from(*v)))
+                .collect(),
+        )
+    }
+
+    fn hash(value: &Value) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn equal_maps_hash_the_same_in_any_order() {
+        let l = map(&[(1, "a"), (2, "b"), (3, "c")]);
+        let r = map(&[(3, "c"), (1, "a"), (2, "b")]);
+        assert_eq!(l, r);
+        assert_eq!(l.cmp(&r), Ordering::Equal);
+        assert_eq!(hash(&l), hash(&r));
+        assert_ne!(hash(&l), hash(&map(&[(1, "a"), (2, "b"), (3, "d")])));
+        // so maps work as keys, even inside other values
+        let keys = HashMap::from([(Value::from((l, Value::from(1))), "found")]);
+        assert_eq!(keys.get(&Value::from((r, Value::from(1)))), Some(&"found"));
+    }
+
+    #[test]
+    fn other_values_order_by_contents() {
+        assert!(Value::from(false) < Value::from(true));
+        assert!(Value::from("ab") < Value::from("b"));
+        assert!(Value::from("a") < Value::from("ab"));
+        let pair = |l: i64, r: i64| Value::from((Value::from(l), Value::from(r)));
+        assert!(pair(1, 9) < pair(2, 0));
+        assert!(pair(1, 1) < pair(1, 2));
+        let list = |v: &[i64]| Value::List(v.iter().map(|n| Value::from(*n)).collect());
+        assert!(list(&[1, 2]) < list(&[1, 3]));
+        assert!(list(&[1]) < list(&[1, 0]));
+        assert!(map(&[(1, "a")]) < map(&[(1, "b")]));
+        assert!(map(&[(2, "a")]) > map(&[(1, "a"), (3, "a")]));
+    }
+
+    #[test]
+    fn maps_keep_insertion_order() {
+        let m = map(&[(3, "c"), (1, "a"), (2, "b")]);
+        assert_eq!(m.to_string(), r#"{3:"c",1:"a",2:"b"}"#);
+        // new keys go on the end, and existing ones keep their place
+        let added = m.clone().checked_add(map(&[(4, "d"), (3, "z")])).unwrap();
+        assert_eq!(added.to_string(), r#"{3:"z",1:"a",2:"b",4:"d"}"#);
+        let Value::List(pairs) = m.into_list().unwrap() else {
+            panic!("expected a list");
+        };
+        let keys: Vec<String> = pairs
+            .iter()
+            .map(|pair| match pair {
+                Value::Pair(k, _) => k.to_string(),
+                v => panic!("expected a pair, got {v}"),
+            })
+            .collect();
+        assert_eq!(keys, ["3", "1", "2"]);
+    }
+}
@@ -0,0 +1,877 @@
+// C source for a compiled program, as a standalone program for `cc` with no dependencies.
+// the C program keeps the tape like `CompactTape` does: the groups bonded to each backbone carbon, and the current one.
+// literals are constants, so each literal's printed value and `Fm` line are worked out here into a table,
+// and groups `Db` makes are integers and rationals, which the C program divides in 64 bits,
+// the way the interpreter does without the `bigint` feature.
+// named equations are C functions, and errors are the interpreter's messages, with holes for what is only known while running.
+// it has no snapshots or host functions, so `Bk` does nothing and host functions are unknown instructions.
+// it can't write the formula of a group `Db` made, or divide pairs, maps or numbers past 64 bits,
+// so `to_c()` refuses programs that might.
+
+use std::{fmt::Write, ops::Range};
+
+use crate::{
+    eval::{
+        number,
+        traits::Valuable,
+        value::{Value, ValueError},
+    },
+    lex::tok::{Token, Type},
+    run::{
+        bytecode::{Bytecode, Direction, Instruction, Op},
+        interpreter::{formula_line, printed, unknown_instruction, MAX_DEPTH},
+        runtime_error::{Reason, RuntimeError},
+    },
+};
+
+// stands in for what an error message only knows while running
+const HOLE: char = '\0';
+
+struct C<'a> {
+    code: &'a Bytecode,
+    // the body of the function being written
+    body: String,
+    // whether any group is ever bonded. if not, nothing is ever bonded to any carbon,
+    // and instructions that print or take groups are written out as what they do to an empty carbon.
+    bonds: bool,
+    // the parts of the runtime the program uses, so unused ones aren't emitted
+    values: bool,
+    formulas: bool,
+    adds: bool,
+    moves: bool,
+    calls: bool,
+    fail: bool,
+    // the messages of each `Db`, as C initialisers
+    divisions: Vec<String>,
+    // the first instruction that can't be compiled, if any
+    unsupported: Option<RuntimeError>,
+}
+
+impl Bytecode {
+    pub fn to_c(&self) -> Result<String, RuntimeError> {
+        let mut gen = C {
+            code: self,
+            body: String::new(),
+            bonds: self.literals.iter().any(|literal| literal.is_ok()),
+            values: false,
+            formulas: false,
+            adds: false,
+            moves: false,
+            calls: false,
+            fail: false,
+            divisions: Vec::new(),
+            unsupported: None,
+        };
+        gen.check();
+        gen.ops(0..self.main, 1);
+        let main = std::mem::take(&mut gen.body);
+        let definitions: Vec<String> = self
+            .definitions
+            .iter()
+            .map(|definition| {
+                gen.ops(definition.ops.clone(), 1);
+                std::mem::take(&mut gen.body)
+            })
+            .collect();
+        if let Some(err) = gen.unsupported {
+            return Err(err);
+        }
+        let divide = !gen.divisions.is_empty();
+        let tape = gen.bonds || gen.adds || gen.moves;
+
+        let mut c = String::new();
+        writeln!(c, "// generated by `er build --target c`").unwrap();
+        if divide {
+            writeln!(c, "#include <limits.h>").unwrap();
+        }
+        if gen.fail {
+            writeln!(c, "#include <stdarg.h>").unwrap();
+        }
+        writeln!(c, "#include <stdio.h>").unwrap();
+        writeln!(c, "#include <stdlib.h>").unwrap();
+        if gen.adds {
+            writeln!(c, "#include <string.h>").unwrap();
+        }
+        writeln!(c).unwrap();
+        if gen.fail {
+            c.push_str(FAIL);
+        }
+        if tape {
+            c.push_str(TAPE);
+        }
+        if gen.bonds {
+            writeln!(c, "static const struct literal literals[] = {{").unwrap();
+            for literal in self.literals.iter().flatten() {
+                let value = printed(literal);
+                let formula = formula_line(literal);
+                let (numeric, numer, denom) = numeric(&literal.value()).unwrap_or((0, 0, 1));
+                writeln!(
+                    c,
+                    "    {{{}, {}, {}, {}, {}, {numeric}, {{{}, {}}}}},",
+                    c_string(&value),
+                    value.len(),
+                    c_string(&formula),
+                    formula.len(),
+                    c_string(&literal.value().to_string()),
+                    c_integer(numer),
+                    c_integer(denom),
+                )
+                .unwrap();
+            }
+            writeln!(c, "}};").unwrap();
+            writeln!(c).unwrap();
+            c.push_str(BOND);
+        }
+        if gen.adds {
+            c.push_str(ADD);
+        }
+        if gen.moves {
+            c.push_str(MOVE);
+        }
+        if gen.values || divide {
+            c.push_str(SHOW);
+        }
+        if gen.values {
+            c.push_str(VALUES);
+        }
+        if gen.formulas {
+            c.push_str(FORMULAS);
+        }
+        if divide {
+            c.push_str(DIVIDE);
+            writeln!(c, "static const struct division division[] = {{").unwrap();
+            for division in &gen.divisions {
+                writeln!(c, "    {division},").unwrap();
+            }
+            writeln!(c, "}};").unwrap();
+            writeln!(c).unwrap();
+        }
+        if gen.calls {
+            writeln!(
+                c,
+                "// how deep calls into definitions can nest, like in the interpreter"
+            )
+            .unwrap();
+            writeln!(c, "static const size_t max_depth = {MAX_DEPTH};").unwrap();
+            c.push_str(CALL);
+        }
+        for (idx, definition) in self.definitions.iter().enumerate() {
+            writeln!(c, "// `{}`", definition.symbol).unwrap();
+            writeln!(c, "static void definition{idx}(void);").unwrap();
+        }
+        if !self.definitions.is_empty() {
+            writeln!(c).unwrap();
+        }
+        writeln!(c, "int main(void) {{").unwrap();
+        if tape {
+            writeln!(c, "    start();").unwrap();
+        }
+        c.push_str(&main);
+        writeln!(c, "    return 0;").unwrap();
+        writeln!(c, "}}").unwrap();
+        for (idx, body) in definitions.iter().enumerate() {
+            writeln!(c).unwrap();
+            writeln!(c, "static void definition{idx}(void) {{").unwrap();
+            c.push_str(body);
+            writeln!(c, "}}").unwrap();
+        }
+        Ok(c)
+    }
+}
+
+impl<'a> C<'a> {
+    // refuses programs that might need what the C program can't do
+    fn check(&mut self) {
+        let code = self.code;
+        let run = |wanted: Instruction| {
+            code.ops.iter().find_map(|op| match op {
+                Op::Run(instruction, token) if *instruction == wanted => Some(*token),
+                _ => None,
+            })
+        };
+        let (Some(db), true) = (run(Instruction::Db), self.bonds) else {
+            return;
+        };
+        if let Some(fm) = run(Instruction::Fm) {
+            self.unsupported(fm, "`Fm` in a program with `Db`");
+        }
+        let literals = code
+            .literals
+            .iter()
+            .flatten()
+            .map(|literal| literal.value());
+        for value in literals {
+            match value {
+                Value::Pair(..) | Value::Map(_) => {
+                    self.unsupported(db, "`Db` in a program with pairs or maps")
+                }
+                Value::Number(_) | Value::Rational(_) if numeric(&value).is_none() => {
+                    self.unsupported(db, "`Db` in a program with numbers past 64 bits")
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn ops(&mut self, ops: Range<usize>, depth: usize) {
+        let code = self.code;
+        let indent = "    ".repeat(depth);
+        let mut pc = ops.start;
+        while pc < ops.end {
+            match code.ops[pc] {
+                Op::Run(instruction, token) => match (instruction, self.bonds) {
+                    (Instruction::Na(direction), _) => {
+                        self.moves = true;
+                        self.fail = true;
+                        let err = RuntimeError::new(
+                            Reason::OffTape(direction),
+                            code.tokens[token].clone(),
+                        );
+                        let down = (direction == Direction::Down) as u8;
+                        let off = c_string(&err.to_string());
+                        writeln!(self.body, "{indent}move({down}, {off});").unwrap();
+                    }
+                    (Instruction::K(direction), _) => {
+                        self.adds = true;
+                        let after = (direction == Direction::Down) as u8;
+                        writeln!(self.body, "{indent}add_carbon({after});").unwrap();
+                    }
+                    (Instruction::Kr | Instruction::Xn, true) => {
+                        self.values = true;
+                        let newline = (instruction == Instruction::Xn) as u8;
+                        writeln!(self.body, "{indent}print_values({newline});").unwrap();
+                    }
+                    (Instruction::Xn, false) => {
+                        writeln!(self.body, "{indent}putchar('\\n');").unwrap()
+                    }
+                    (Instruction::Fm, true) => {
+                        self.formulas = true;
+                        writeln!(self.body, "{indent}print_formulas();").unwrap();
+                    }
+                    (Instruction::Kr | Instruction::Fm | Instruction::Bk, _) => {}
+                    (Instruction::Db, true) => {
+                        self.fail = true;
+                        let division = self.division(&code.tokens[token]);
+                        writeln!(self.body, "{indent}divide(&division[{division}]);").unwrap();
+                    }
+                    (Instruction::Db, false) => {
+                        let err = arity(&code.tokens[token], 0);
+                        self.fail(&indent, &format(&err, &[]));
+                    }
+                    // the compiler makes calls into `Call` ops
+                    (Instruction::Call(_), _) => unreachable!(),
+                    (Instruction::Host(_) | Instruction::Unknown, _) => {
+                        let err = unknown_instruction(&code.tokens[token]);
+                        self.fail(&indent, &format(&err, &[]));
+                    }
+                },
+                Op::Bond(literal) => match &code.literals[literal] {
+                    Ok(_) => {
+                        // failed literals have no entry in the table
+                        let literal = code.literals[..literal].iter().flatten().count();
+                        writeln!(self.body, "{indent}bond_literal({literal});").unwrap();
+                    }
+                    Err(err) => self.fail(&indent, &format(err, &[])),
+                },
+                Op::Repeat { times, len } => {
+                    let i = format!("i{depth}");
+                    writeln!(
+                        self.body,
+                        "{indent}for (size_t {i} = 0; {i} < {times}; {i}++) {{"
+                    )
+                    .unwrap();
+                    self.ops(pc + 1..pc + 1 + len, depth + 1);
+                    writeln!(self.body, "{indent}}}").unwrap();
+                    pc += len;
+                }
+                Op::Call(definition, token) => {
+                    self.calls = true;
+                    self.fail = true;
+                    let reason = Reason::Depth {
+                        symbol: code.definitions[definition].symbol.clone(),
+                        depth: MAX_DEPTH,
+                    };
+                    let err = RuntimeError::new(reason, code.tokens[token].clone());
+                    let too_deep = c_string(&err.to_string());
+                    writeln!(
+                        self.body,
+                        "{indent}call(definition{definition}, {too_deep});"
+                    )
+                    .unwrap();
+                }
+                Op::Fail(error) => self.fail(&indent, &format(&code.errors[error], &[])),
+            }
+            pc += 1;
+        }
+    }
+
+    // the messages a `Db` fails with, as an index into the table of them
+    fn division(&mut self, token: &Token) -> usize {
+        let hole = String::from(HOLE);
+        let value = |err: ValueError| RuntimeError::new(Reason::Value(err), token.clone());
+        let operands = [(hole.as_str(), "%s / %s")];
+        // the values of an unsupported division are shown like any other value, so the hole is a string
+        let shown = Value::String(vec![HOLE]).to_string();
+        let unsupported = value(ValueError::Unsupported {
+            op: "Div",
+            l: Box::new(Value::String(vec![HOLE])),
+            r: Box::new(Value::String(vec![HOLE])),
+        });
+        self.divisions.push(format!(
+            "{{{}, {}, {}, {}}}",
+            format(
+                &arity(token, usize::MAX),
+                &[(&usize::MAX.to_string(), "%zu")]
+            ),
+            format(&value(ValueError::DivisionByZero(hole.clone())), &operands),
+            format(&value(ValueError::Overflow(hole.clone())), &operands),
+            format(&unsupported, &[(&shown, "%s")]),
+        ));
+        self.divisions.len() - 1
+    }
+
+    fn unsupported(&mut self, token: usize, what: &str) {
+        let token = &self.code.tokens[token];
+        let reason = Reason::Unsupported(format!("{what} when compiling to C"));
+        let err = RuntimeError::new(reason, token.clone());
+        self.unsupported.get_or_insert(err);
+    }
+
+    fn fail(&mut self, indent: &str, message: &str) {
+        self.fail = true;
+        writeln!(self.body, "{indent}fail({message});").unwrap();
+    }
+}
+
+// what the interpreter fails with when `Db` has fewer than 2 groups to take
+fn arity(token: &Token, bonded: usize) -> RuntimeError {
+    let symbol = match &token.token {
+        Type::Element(symbol) => symbol.to_string(),
+        token => token.to_string(),
+    };
+    let reason = Reason::Arity {
+        symbol,
+        arity: 2,
+        bonded,
+    };
+    RuntimeError::new(reason, token.clone())
+}
+
+// whether a value is an integer or rational, with its numerator and denominator, if they fit in 64 bits
+fn numeric(value: &Value) -> Option<(u8, i64, i64)> {
+    match value {
+        Value::Number(n) => Some((1, number::to_i64(n)?, 1)),
+        Value::Rational(r) => Some((1, number::to_i64(r.numer())?, number::to_i64(r.denom())?)),
+        _ => None,
+    }
+}
+
+// an error's message as a C string for `fail`, which is a format string,
+// with each hole filled by the conversion that prints what goes there
+fn format(err: &RuntimeError, holes: &[(&str, &str)]) -> String {
+    let mut message = err.to_string().replace('%', "%%");
+    for (hole, conversion) in holes {
+        message = message.replace(hole, conversion);
+    }
+    c_string(&message)
+}
+
+// stops the run like the interpreter does, after everything printed before it.
+// the message is a format string, for the parts only known while running.
+const FAIL: &str = r#"static void fail(const char *format, ...) {
+    va_list args;
+    fflush(stdout);
+    va_start(args, format);
+    vfprintf(stderr, format, args);
+    va_end(args);
+    fputc('\n', stderr);
+    exit(EXIT_FAILURE);
+}
+
+"#;
+
+const TAPE: &str = r#"// an integer, or a rational in lowest terms with a positive denominator
+struct ratio {
+    long long numer, denom;
+};
+
+// a literal's group, worked out when the program was compiled
+struct literal {
+    // the value as `Kr` prints it, which may have NULs in it
+    const char *value;
+    size_t value_len;
+    // the line `Fm` prints
+    const char *formula;
+    size_t formula_len;
+    // the value as errors show it
+    const char *shown;
+    // whether it is an integer or rational, and which
+    int numeric;
+    struct ratio ratio;
+};
+
+// a group bonded to a carbon, which is a literal, or a value `Db` worked out
+struct group {
+    const struct literal *literal;
+    struct ratio ratio;
+};
+
+// the groups bonded to a carbon, in the order they were bonded
+struct carbon {
+    struct group *groups;
+    size_t len, cap;
+};
+
+// the backbone in order, and where the current carbon is in it
+static struct carbon *carbons;
+static size_t carbons_len, carbons_cap, current;
+
+// room for one more item at the end of a growing array
+static void *reserve(void *items, size_t len, size_t *cap, size_t size) {
+    if (len < *cap) {
+        return items;
+    }
+    *cap = *cap ? *cap * 2 : 8;
+    items = realloc(items, *cap * size);
+    if (!items) {
+        fputs("out of memory\n", stderr);
+        exit(EXIT_FAILURE);
+    }
+    return items;
+}
+
+// the tape starts as a single carbon with nothing bonded to it
+static void start(void) {
+    carbons = reserve(NULL, 0, &carbons_cap, sizeof *carbons);
+    carbons[0].groups = NULL;
+    carbons[0].len = carbons[0].cap = 0;
+    carbons_len = 1;
+}
+
+"#;
+
+const BOND: &str = r#"static void bond(struct group group) {
+    struct carbon *carbon = &carbons[current];
+    carbon->groups = reserve(carbon->groups, carbon->len, &carbon->cap, sizeof *carbon->groups);
+    carbon->groups[carbon->len++] = group;
+}
+
+static void bond_literal(size_t literal) {
+    struct group group;
+    group.literal = &literals[literal];
+    group.ratio = literals[literal].ratio;
+    bond(group);
+}
+
+"#;
+
+// `K`
+const ADD: &str = r#"// adds an empty carbon next to the current one, which stays current
+static void add_carbon(int after) {
+    size_t at = after ? current + 1 : current;
+    carbons = reserve(carbons, carbons_len, &carbons_cap, sizeof *carbons);
+    memmove(&carbons[at + 1], &carbons[at], (carbons_len - at) * sizeof *carbons);
+    carbons[at].groups = NULL;
+    carbons[at].len = carbons[at].cap = 0;
+    carbons_len++;
+    if (!after) {
+        current++;
+    }
+}
+
+"#;
+
+// `Na`
+const MOVE: &str = r#"// moves to the next carbon, failing with `off` if there isn't one
+static void move(int down, const char *off) {
+    if (down ? current + 1 == carbons_len : current == 0) {
+        fail("%s", off);
+    }
+    current = down ? current + 1 : current - 1;
+}
+
+"#;
+
+// groups `Db` made are only ever integers and rationals, which print the way errors show them
+const SHOW: &str = r#"// the value as errors show it, written to `out` if it isn't a literal
+static const char *show(char out[48], const struct group *group) {
+    if (group->literal) {
+        return group->literal->shown;
+    }
+    if (group->ratio.denom == 1) {
+        sprintf(out, "%lld", group->ratio.numer);
+    } else {
+        sprintf(out, "%lld/%lld", group->ratio.numer, group->ratio.denom);
+    }
+    return out;
+}
+
+"#;
+
+const VALUES: &str = r#"static void print_values(int newline) {
+    const struct carbon *carbon = &carbons[current];
+    char shown[48];
+    for (size_t i = 0; i < carbon->len; i++) {
+        const struct group *group = &carbon->groups[i];
+        if (i > 0) {
+            putchar('\n');
+        }
+        if (group->literal) {
+            fwrite(group->literal->value, 1, group->literal->value_len, stdout);
+        } else {
+            fputs(show(shown, group), stdout);
+        }
+    }
+    if (newline) {
+        putchar('\n');
+    }
+}
+
+"#;
+
+// only literals have a formula, and programs with `Db` can't use `Fm`
+const FORMULAS: &str = r#"static void print_formulas(void) {
+    const struct carbon *carbon = &carbons[current];
+    for (size_t i = 0; i < carbon->len; i++) {
+        const struct literal *literal = carbon->groups[i].literal;
+        fwrite(literal->formula, 1, literal->formula_len, stdout);
+    }
+}
+
+"#;
+
+// the same steps as `Ratio::checked_div` on `i64`, so it overflows where the interpreter does
+const DIVIDE: &str = r#"// the messages a `Db` fails with
+struct division {
+    const char *arity, *zero, *overflow, *unsupported;
+};
+
+static unsigned long long magnitude(long long n) {
+    return n < 0 ? 0ULL - (unsigned long long)n : (unsigned long long)n;
+}
+
+// never negative, except that the gcd of the smallest integer and 0 or itself wraps around to it
+static long long gcd(long long a, long long b) {
+    unsigned long long m = magnitude(a), n = magnitude(b);
+    while (n) {
+        unsigned long long r = m % n;
+        m = n;
+        n = r;
+    }
+    return m > (unsigned long long)LLONG_MAX ? LLONG_MIN : (long long)m;
+}
+
+static int checked_mul(long long a, long long b, long long *product) {
+    if (a > 0 ? (b > 0 ? a > LLONG_MAX / b : b < LLONG_MIN / a)
+              : (b > 0 ? a < LLONG_MIN / b : a != 0 && b < LLONG_MAX / a)) {
+        return 0;
+    }
+    *product = a * b;
+    return 1;
+}
+
+// l / r, or 0 if it overflows. r isn't 0.
+static int checked_div(struct ratio l, struct ratio r, struct ratio *quotient) {
+    long long numer, denom, g;
+    if (l.denom == r.denom) {
+        numer = l.numer;
+        denom = r.numer;
+    } else if (l.numer == r.numer) {
+        numer = r.denom;
+        denom = l.denom;
+    } else {
+        long long gcd_ac = gcd(l.numer, r.numer), gcd_bd = gcd(l.denom, r.denom);
+        if (!checked_mul(l.numer / gcd_ac, r.denom / gcd_bd, &numer)
+            || !checked_mul(l.denom / gcd_bd, r.numer / gcd_ac, &denom)) {
+            return 0;
+        }
+    }
+    if (numer == 0) {
+        quotient->numer = 0;
+        quotient->denom = 1;
+        return 1;
+    }
+    if (numer == denom) {
+        quotient->numer = quotient->denom = 1;
+        return 1;
+    }
+    g = gcd(numer, denom);
+    numer /= g;
+    denom /= g;
+    if (denom < 0 && (!checked_mul(numer, -1, &numer) || !checked_mul(denom, -1, &denom))) {
+        return 0;
+    }
+    quotient->numer = numer;
+    quotient->denom = denom;
+    return 1;
+}
+
+// divides the second to last group bonded to the current carbon by the last, bonding the quotient in their place
+static void divide(const struct division *messages) {
+    struct carbon *carbon = &carbons[current];
+    const struct group *l, *r;
+    struct group quotient;
+    char ls[48], rs[48];
+    if (carbon->len < 2) {
+        fail(messages->arity, carbon->len);
+    }
+    l = &carbon->groups[carbon->len - 2];
+    r = &carbon->groups[carbon->len - 1];
+    if ((l->literal && !l->literal->numeric) || (r->literal && !r->literal->numeric)) {
+        fail(messages->unsupported, show(ls, l), show(rs, r));
+    }
+    if (r->ratio.numer == 0) {
+        fail(messages->zero, show(ls, l), show(rs, r));
+    }
+    quotient.literal = NULL;
+    if (!checked_div(l->ratio, r->ratio, &quotient.ratio)) {
+        fail(messages->overflow, show(ls, l), show(rs, r));
+    }
+    carbon->len -= 2;
+    bond(quotient);
+}
+
+"#;
+
+const CALL: &str = r#"// the calls into definitions the program is in
+static size_t depth;
+
+// calls a definition, failing with `too_deep` instead if that nests calls deeper than the interpreter does
+static void call(void (*definition)(void), const char *too_deep) {
+    if (depth == max_depth) {
+        fail("%s", too_deep);
+    }
+    depth++;
+    definition();
+    depth--;
+}
+
+"#;
+
+// a `long long` constant. the smallest one has to be worked out, since the constant after a minus sign doesn't fit.
+fn c_integer(n: i64) -> String {
+    match n {
+        i64::MIN => format!("({}LL - 1)", i64::MIN + 1),
+        n => format!("{n}LL"),
+    }
+}
+
+// a C string literal of the UTF-8 bytes of `s`.
+// anything else that isn't printable ASCII is a 3 digit octal escape, so it can't run into the next character,
+// and `?` is escaped so it can't start a trigraph.
+fn c_string(s: &str) -> String {
+    let mut c = String::from("\"");
+    for b in s.bytes() {
+        match b {
+            b'"' | b'\\' | b'?' => write!(c, "\\{}", b as char).unwrap(),
+            b'\n' => c.push_str("\\n"),
+            b'\t' => c.push_str("\\t"),
+            b' '..=b'~' => c.push(b as char),
+            _ => write!(c, "\\{b:03o}").unwrap(),
+        }
+    }
+    c.push('"');
+    c
+}
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        env, fs,
+        process::{self, Command},
+    };
+
+    use super::*;
+    use crate::{lex::lexer::Lexer, par::parser::Parser, run::interpreter::Interpreter};
+
+    fn compile(source: &str) -> Bytecode {
+        let tokens = Lexer::new(source.to_string()).all_tokens();
+        let program = Parser::new(tokens).parse().unwrap();
+        crate::run::bytecode::compile(&program, &HashMap::new())
+    }
+
+    // what `er run` prints to standard out and standard error, and whether it succeeds
+    fn interpreted(source: &str) -> (Vec<u8>, String, bool) {
+        let mut out = Vec::new();
+        let ran = Interpreter::new(&mut out).run(source);
+        let err = ran.as_ref().err().map(|err| format!("{err}\n"));
+        (out, err.unwrap_or_default(), ran.is_ok())
+    }
+
+    // the same, for the C program, or `None` without a C compiler
+    fn compiled(source: &str, name: &str) -> Option<(Vec<u8>, String, bool)> {
+        let dir = env::temp_dir().join(format!("er-c-{}-{name}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (c, exe) = (dir.join("prog.c"), dir.join("prog"));
+        fs::write(&c, compile(source).to_c().unwrap()).unwrap();
+        let built = Command::new("cc")
+            .args(["-std=c99", "-Wall", "-Wextra", "-pedantic", "-Werror", "-o"])
+            .arg(&exe)
+            .arg(&c)
+            .output();
+        let ran = match built {
+            Err(_) => None,
+            Ok(built) => {
+                assert!(
+                    built.status.success(),
+                    "{source}: {}",
+                    String::from_utf8_lossy(&built.stderr)
+                );
+                let ran = Command::new(&exe).output().unwrap();
+                let err = String::from_utf8(ran.stderr).unwrap();
+                Some((ran.stdout, err, ran.status.success()))
+            }
+        };
+        fs::remove_dir_all(&dir).unwrap();
+        ran
+    }
+
+    fn same(name: &str, programs: &[&str]) {
+        for (i, source) in programs.iter().enumerate() {
+            let Some(compiled) = compiled(source, &format!("{name}-{i}")) else {
+                eprintln!("no `cc` to compile with, skipping");
+                return;
+            };
+            assert_eq!(compiled, interpreted(source), "{source}");
+        }
+    }
+
+    #[test]
+    fn prints_like_the_interpreter() {
+        same(
+            "prints",
+            &[
+                "H^3Xn -> H\n",
+                "Xn -> H\n",
+                "H^1Kr + H^2Xn -> H\n",
+                "HOC_2 + HOLiBe + Fm -> H\n",
+                "3H^1 + 2Xn_2 -> H\n",
+                "H$\"O[Li]\" + Xn + Fm -> H\n",
+                "H$\"O[Uue]\" + Fm -> H\n",
+                // a string with a control character in it
+                "H$\"S(O[Li])\" + Xn -> H\n",
+                "H^1Bk + Xn -> H\n",
+                "H^3Xn -> H\nH^4Xn -> H\n",
+            ],
+        );
+    }
+
+    #[test]
+    fn fails_like_the_interpreter() {
+        same(
+            "fails",
+            &[
+                "H^1Xn + Zz + Xn -> H\n",
+                "Kr + 2Zz -> H\n",
+                "H^1Xn + H$\"O[Li][Be]\" + Xn -> H\n",
+                "H^1Kr + HOFe_999999999999999999999999 + Xn -> H\n",
+            ],
+        );
+    }
+
+    #[test]
+    fn divides_like_the_interpreter() {
+        same(
+            "divides",
+            &[
+                "H^6H^4Db + Xn -> H\n",
+                "H^6H^3Db + Xn -> H\n",
+                "H^6H^4Db + H^3Db + Xn -> H\n",
+                "H^1H^-3Db + H^-2Db + Xn -> H\n",
+                "H^0H^5Db + Xn -> H\n",
+                "HTrDb + Xn -> H\n",
+                "H^1Xn + H^6H^0Db + Xn -> H\n",
+                "H^1H\"a\"Db -> H\n",
+                "H^1Db -> H\n",
+                "Db -> H\n",
+                "H^9223372036854775807H^1Db + Xn -> H\n",
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "bigint"))]
+    fn overflows_like_the_interpreter() {
+        same(
+            "overflows",
+            &[
+                "H^-9223372036854775808H^-1Db + Xn -> H\n",
+                "H^2H^3Db + H^9223372036854775807Db + Xn -> H\n",
+            ],
+        );
+    }
+
+    #[test]
+    fn moves_and_calls_like_the_interpreter() {
+        same(
+            "moves",
+            &[
+                "KBe + NaBe + H^1Xn + NaLi + H^2Xn + NaBe + Xn -> H\n",
+                "KLi + H^1Kr + NaBe + H^2Xn -> H\n",
+                "NaLi -> H\n",
+                "KBe + NaBe_2 -> H\n",
+                "Uue: H^2Db -> H\nH^3Uue + Xn -> H\n",
+                "Uue: H^1Xn + Uuo -> H\nUuo: Uue -> H\nUue -> H\n",
+            ],
+        );
+    }
+
+    // every sample prints what it should, and the same when compiled to C
+    #[test]
+    fn runs_samples() {
+        let samples = [
+            (
+                "booleans",
+                include_str!("../../sample/booleans.er"),
+                "true\nfalse\ntrue\nfalse\n",
+            ),
+            (
+                "halves",
+                include_str!("../../sample/halves.er"),
+                "3/2\n7/2\n3/2\n",
+            ),
+            (
+                "hello-world",
+                include_str!("../../sample/hello-world.er"),
+                "Hello, World!\n",
+            ),
+            (
+                "numbers",
+                include_str!("../../sample/numbers.er"),
+                "123\n123\n123\n",
+            ),
+        ];
+        for (name, source, printed) in samples {
+            let source = format!("{source}\n");
+            let (out, err, ok) = interpreted(&source);
+            assert_eq!(
+                (String::from_utf8(out).unwrap(), err, ok),
+                (printed.to_string(), String::new(), true),
+                "{name}"
+            );
+            same(name, &[&source]);
+        }
+    }
+
+    #[test]
+    fn refuses_instructions_it_cannot_compile() {
+        for source in [
+            "H^1H^2Db + Fm -> H\n",
+            "H^1H^2Db + H$\"N(O[Li])O[Be]\" + Xn -> H\n",
+            #[cfg(feature = "bigint")]
+            "H^1H^99999999999999999999Db -> H\n",
+        ] {
+            let err = compile(source).to_c().unwrap_err();
+            assert!(matches!(*err.reason, Reason::Unsupported(_)), "{source}");
+        }
+    }
+
+    #[test]
+    fn escapes_strings() {
+        assert_eq!(c_string("a\"b\\c"), r#""a\"b\\c""#);
+        // `??=` would be a trigraph
+        assert_eq!(c_string("??="), r#""\?\?=""#);
+        assert_eq!(c_string("\n\t\u{3}1"), r#""\n\t\0031""#);
+        assert_eq!(c_string("é"), r#""\303\251""#);
+    }
+}
@@ -0,0 +1,189 @@
+// Graphviz DOT rendering of molecules and the tape.
+// every atom is a node labelled with its element, and every nested functional group is a cluster.
+
+use std::{collections::HashMap, fmt::Write};
+
+use petgraph::stable_graph::NodeIndex;
+
+use crate::eval::{
+    atom_like::AtomLike,
+    atoms::Atoms,
+    functional_groups::{alkane::Alkane, FunctionalGroup},
+    molecule::Molecule,
+};
+
+const BACKBONE: &str = "style=filled, fillcolor=lightgrey";
+const POINTER: &str = "penwidth=3, color=red";
+
+struct Dot {
+    out: String,
+    next_id: usize,
+    // the current carbon of the tape, if rendering the tape
+    pointer: Option<NodeIndex>,
+}
+
+impl Dot {
+    fn new(name: &str) -> Dot {
+        let mut out = String::new();
+        writeln!(out, "graph {name} {{").unwrap();
+        writeln!(out, "    node [shape=circle];").unwrap();
+        Dot {
+            out,
+            next_id: 0,
+            pointer: None,
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.out.push_str("}\n");
+        self.out
+    }
+
+    // write the atoms and bonds of a molecule, returning the node id its head ended up as.
+    // `backbone` is the carbons to highlight, and `top` is whether this is the outermost molecule.
+    fn atoms(
+        &mut self,
+        atoms: &Atoms,
+        backbone: &[NodeIndex],
+        top: bool,
+        indent: usize,
+    ) -> Option<String> {
+        let pad = "    ".repeat(indent);
+        let mut ids: HashMap<NodeIndex, String> = HashMap::new();
+        for idx in atoms.atoms().node_indices() {
+            match atoms.atoms().node_weight(idx).unwrap() {
+                Molecule::E(e) => {
+                    let id = format!("n{}", self.next_id);
+                    self.next_id += 1;
+                    let mut attrs = vec![format!("label=\"{e}\"")];
+                    if backbone.contains(&idx) {
+                        attrs.push(BACKBONE.to_string());
+                    }
+                    if top && self.pointer == Some(idx) {
+                        attrs.push(POINTER.to_string());
+                    }
+                    writeln!(self.out, "{pad}{id} [{}];", attrs.join(", ")).unwrap();
+                    ids.insert(idx, id);
+                }
+                Molecule::F(fg) => {
+                    if let Some(head) = self.group(fg, indent) {
+                        ids.insert(idx, head);
+                    }
+                }
+            }
+        }
+        for edge in atoms.atoms().edge_indices() {
+            let (l, r) = atoms.atoms().edge_endpoints(edge).unwrap();
+            if let (Some(l), Some(r)) = (ids.get(&l), ids.get(&r)) {
+                writeln!(self.out, "{pad}{l} -- {r};").unwrap();
+            }
+        }
+        ids.get(&atoms.head).cloned()
+    }
+
+    // a nested functional group, as a cluster labelled with its kind and value
+    fn group(&mut self, fg: &FunctionalGroup, indent: usize) -> Option<String> {
+        let pad = "    ".repeat(indent);
+        let cluster = self.next_id;
+        self.next_id += 1;
+        writeln!(self.out, "{pad}subgraph cluster_{cluster} {{").unwrap();
+        let label = fg.to_string().replace('\\', "\\\\").replace('"', "\\\"");
+        writeln!(self.out, "{pad}    label=\"{label}\";").unwrap();
+        let backbone: Vec<NodeIndex> = match fg {
+            FunctionalGroup::Alkane(alk) => alk.backbone().copied().collect(),
+            _ => Vec::new(),
+        };
+        let head = self.atoms(fg.get_atoms(), &backbone, false, indent + 1);
+        writeln!(self.out, "{pad}}}").unwrap();
+        head
+    }
+}
+
+impl Atoms {
+    pub fn to_dot(&self) -> String {
+        let mut dot = Dot::new("molecule");
+        dot.atoms(self, &[], true, 1);
+        dot.finish()
+    }
+}
+
+impl Alkane {
+    // the tape, with its backbone highlighted and its current carbon outlined
+    pub fn to_dot(&self) -> String {
+        let mut dot = Dot::new("tape");
+        // atoms are written in index order
+        let alk = self.in_bond_order();
+        dot.pointer = Some(alk.current_atom);
+        let backbone: Vec<NodeIndex> = alk.backbone().copied().collect();
+        dot.atoms(alk.get_atoms(), &backbone, true, 1);
+        dot.finish()
+    }
+}
+
+impl FunctionalGroup {
+    pub fn to_dot(&self) -> String {
+        let mut dot = Dot::new("molecule");
+        dot.group(self, 1);
+        dot.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::value::Value;
+
+    #[test]
+    fn labels_atoms_and_bonds() {
+        assert_eq!(
+            Atoms::from_smiles("ON").unwrap().to_dot(),
+            "graph molecule {
+    node [shape=circle];
+    n0 [label=\"O\"];
+    n1 [label=\"N\"];
+    n0 -- n1;
+}
+"
+        );
+    }
+
+    #[test]
+    fn marks_the_backbone_and_the_current_carbon() {
+        let mut tape = Alkane::new_n_alkane(1);
+        tape.add_functional_group(FunctionalGroup::from(Value::from(3)));
+        tape.move_down();
+        assert_eq!(
+            tape.to_dot(),
+            "graph tape {
+    node [shape=circle];
+    n0 [label=\"C\", style=filled, fillcolor=lightgrey];
+    n1 [label=\"C\", style=filled, fillcolor=lightgrey, penwidth=3, color=red];
+    subgraph cluster_2 {
+        label=\"Ether(3)\";
+        n3 [label=\"O\"];
+        n4 [label=\"Li\"];
+        n3 -- n4;
+    }
+    n0 -- n1;
+    n1 -- n3;
+}
+"
+        );
+    }
+
+    #[test]
+    fn nests_groups_as_clusters() {
+        let dot = FunctionalGroup::from(Value::List(vec![Value::from(1)])).to_dot();
+        assert!(dot.contains("    subgraph cluster_0 {\n        label=\"Alkane([1])\";"));
+        assert!(dot.contains("        subgraph cluster_3 {\n            label=\"Ether(1)\";"));
+        // only the tape has a current carbon
+        assert!(!dot.contains("color=red"));
+    }
+
+    #[test]
+    fn escapes_labels() {
+        let dot = FunctionalGroup::from(Value::String(vec!['"', '\\'])).to_dot();
+        // the sulfide displays as `Sulfide(""\")`
+        assert!(dot.contains(r#"label="Sulfide(\"\"\\\")";"#), "{dot}");
+    }
+}
@@ -0,0 +1,131 @@
+// JSON for values and for the structure of molecules, read back by `import::json`.
+//
+// values use plain JSON where there is one, and a single-key object where there isn't:
+// numbers are integers (or `{"number": "digits"}` if they don't fit in 64 bits),
+// rationals are `{"rational": [n, d]}`, pairs are `{"pair": [l, r]}`,
+// and maps are `{"map": [[k, v], ...]}`, since their keys don't have to be strings.
+//
+// molecules list their atoms, as element symbols or nested groups, and their bonds by position:
+// `{"atoms": ["O", "Li"], "bonds": [[0, 1]], "head": 0}`.
+// functional groups add their `kind`, and whatever else they keep track of.
+
+use std::{borrow::Cow, collections::HashMap};
+
+use petgraph::stable_graph::NodeIndex;
+use serde_json::{json, Map, Value as Json};
+
+use crate::eval::{
+    atom_like::AtomLike, atoms::Atoms, functional_groups::FunctionalGroup, molecule::Molecule,
+    number::Number, value::Value,
+};
+
+impl Value {
+    pub fn to_json(&self) -> Json {
+        match self {
+            Value::Number(n) => number(n),
+            Value::Rational(r) => json!({ "rational": [number(r.numer()), number(r.denom())] }),
+            Value::Boolean(b) => Json::Bool(*b),
+            Value::String(s) => Json::String(s.iter().collect()),
+            Value::Pair(l, r) => json!({ "pair": [l.to_json(), r.to_json()] }),
+            Value::List(v) => Json::Array(v.iter().map(Value::to_json).collect()),
+            Value::Map(m) => {
+                let entries = m
+                    .iter()
+                    .map(|(k, v)| json!([k.to_json(), v.to_json()]))
+                    .collect();
+                json!({ "map": Json::Array(entries) })
+            }
+        }
+    }
+}
+
+fn number(n: &Number) -> Json {
+    match n.to_string().parse::<i64>() {
+        Ok(n) => Json::from(n),
+        Err(_) => json!({ "number": n.to_string() }),
+    }
+}
+
+impl Atoms {
+    pub fn to_json(&self) -> Json {
+        Json::Object(self.json_fields().0)
+    }
+
+    // the fields describing this molecule, and the position each atom was written at
+    fn json_fields(&self) -> (Map<String, Json>, HashMap<NodeIndex, usize>) {
+        let positions: HashMap<NodeIndex, usize> = self
+            .atoms()
+            .node_indices()
+            .enumerate()
+            .map(|(position, idx)| (idx, position))
+            .collect();
+        let atoms: Vec<Json> = self
+            .atoms()
+            .node_indices()
+            .map(|idx| match self.atoms().node_weight(idx).unwrap() {
+                Molecule::E(e) => Json::String(e.to_string()),
+                Molecule::F(fg) => fg.to_json(),
+            })
+            .collect();
+        let bonds: Vec<Json> = self
+            .atoms()
+            .edge_indices()
+            .map(|edge| {
+                let (l, r) = self.atoms().edge_endpoints(edge).unwrap();
+                json!([positions[&l], positions[&r]])
+            })
+            .collect();
+        let mut fields = Map::new();
+        fields.insert(String::from("atoms"), Json::Array(atoms));
+        fields.insert(String::from("bonds"), Json::Array(bonds));
+        if let Some(head) = positions.get(&self.head) {
+            fields.insert(String::from("head"), Json::from(*head));
+        }
+        let mut fillers: Vec<usize> = self
+            .atoms()
+            .node_indices()
+            .filter(|idx| self.is_filler(*idx))
+            .map(|idx| positions[&idx])
+            .collect();
+        if !fillers.is_empty() {
+            fillers.sort();
+            fields.insert(String::from("fillers"), Json::from(fillers));
+        }
+        (fields, positions)
+    }
+}
+
+impl FunctionalGroup {
+    pub fn to_json(&self) -> Json {
+        // atoms are read back in the order they are written, which is index order
+        if let FunctionalGroup::Alkane(alk) = self {
+            if let Cow::Owned(alk) = alk.in_bond_order() {
+                return FunctionalGroup::Alkane(alk).to_json();
+            }
+        }
+        let (mut fields, positions) = self.get_atoms().json_fields();
+        fields.insert(String::from("kind"), Json::from(self.kind()));
+        match self {
+            FunctionalGroup::Alkane(alk) => {
+                let backbone: Vec<usize> = alk.backbone().map(|c| positions[c]).collect();
+                fields.insert(String::from("backbone"), Json::from(backbone));
+                fields.insert(
+                    String::from("current"),
+                    Json::from(positions[&alk.current_atom]),
+                );
+                if let Some(marker) = alk.marker() {
+                    fields.insert(String::from("marker"), Json::from(positions[&marker]));
+                }
+            }
+            FunctionalGroup::Ester(est) => {
+                fields.insert(
+                    String::from("carbonyl"),
+                    Json::from(positions[&est.carbonyl()]),
+                );
+                fields.insert(String::from("alkoxy"), Json::from(positions[&est.alkoxy()]));
+            }
+            _ => {}
+        }
+        Json::Object(fields)
+    }
+}
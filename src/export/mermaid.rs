@@ -0,0 +1,145 @@
+// Mermaid `graph TD` rendering of functional groups, in the style of `docs/functional-groups.md`.
+// every nested functional group is a subgraph labelled with its kind and value.
+
+use std::{collections::HashMap, fmt::Write};
+
+use petgraph::stable_graph::NodeIndex;
+
+use crate::eval::{
+    atom_like::AtomLike, atoms::Atoms, functional_groups::FunctionalGroup, molecule::Molecule,
+    traits::Valuable,
+};
+
+struct Mermaid {
+    out: String,
+    next_id: usize,
+}
+
+impl Mermaid {
+    fn new() -> Mermaid {
+        Mermaid {
+            out: String::from("graph TD\n"),
+            next_id: 0,
+        }
+    }
+
+    fn id(&mut self, prefix: &str) -> String {
+        let id = format!("{prefix}{}", self.next_id);
+        self.next_id += 1;
+        id
+    }
+
+    // write the atoms and bonds of a molecule, returning the node id its head ended up as
+    fn atoms(&mut self, atoms: &Atoms) -> Option<String> {
+        let mut ids: HashMap<NodeIndex, String> = HashMap::new();
+        for idx in atoms.atoms().node_indices() {
+            match atoms.atoms().node_weight(idx).unwrap() {
+                Molecule::E(e) => {
+                    let id = self.id("n");
+                    writeln!(self.out, "{id}[{e}]").unwrap();
+                    ids.insert(idx, id);
+                }
+                Molecule::F(fg) => {
+                    if let Some(head) = self.group(fg, None) {
+                        ids.insert(idx, head);
+                    }
+                }
+            }
+        }
+        for edge in atoms.atoms().edge_indices() {
+            let (l, r) = atoms.atoms().edge_endpoints(edge).unwrap();
+            if let (Some(l), Some(r)) = (ids.get(&l), ids.get(&r)) {
+                writeln!(self.out, "{l} --- {r}").unwrap();
+            }
+        }
+        ids.get(&atoms.head).cloned()
+    }
+
+    // a functional group as a subgraph, optionally bonded to a carbon inside it like in the docs
+    fn group(&mut self, fg: &FunctionalGroup, carbon: Option<&str>) -> Option<String> {
+        let id = self.id("g");
+        let label = format!("{}: {}", fg.kind(), fg.value()).replace('"', "#quot;");
+        writeln!(self.out, "subgraph {id} [\"{label}\"]").unwrap();
+        if let Some(carbon) = carbon {
+            writeln!(self.out, "{carbon}[C]").unwrap();
+        }
+        let head = self.atoms(fg.get_atoms());
+        if let (Some(carbon), Some(head)) = (carbon, &head) {
+            writeln!(self.out, "{carbon} --- {head}").unwrap();
+        }
+        self.out.push_str("end\n");
+        head
+    }
+}
+
+impl FunctionalGroup {
+    // the group bonded to a carbon, as a Mermaid diagram
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = Mermaid::new();
+        let carbon = mermaid.id("c");
+        mermaid.group(self, Some(&carbon));
+        mermaid.out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::value::Value;
+
+    #[test]
+    fn draws_a_group_bonded_to_a_carbon() {
+        assert_eq!(
+            FunctionalGroup::from(Value::from(118)).to_mermaid(),
+            "graph TD
+subgraph g1 [\"Ether: 118\"]
+c0[C]
+n2[O]
+n3[Og]
+n2 --- n3
+c0 --- n2
+end
+"
+        );
+    }
+
+    #[test]
+    fn nests_groups_as_subgraphs() {
+        let pair = Value::Pair(Box::new(Value::from(1)), Box::new(Value::Boolean(true)));
+        assert_eq!(
+            FunctionalGroup::from(pair).to_mermaid(),
+            "graph TD
+subgraph g1 [\"Amine: (1,true)\"]
+c0[C]
+n2[N]
+subgraph g3 [\"Ether: 1\"]
+n4[O]
+n5[H]
+n4 --- n5
+end
+subgraph g6 [\"BorinicAcid: true\"]
+n7[B]
+n8[O]
+n9[H]
+n10[H]
+n7 --- n8
+n8 --- n9
+n7 --- n10
+end
+n2 --- n7
+n2 --- n4
+c0 --- n2
+end
+"
+        );
+    }
+
+    #[test]
+    fn escapes_quotes_in_labels() {
+        let mermaid = FunctionalGroup::from(Value::String(vec!['"'])).to_mermaid();
+        assert!(
+            mermaid.starts_with("graph TD\nsubgraph g1 [\"Sulfide: #quot;#quot;#quot;\"]\n"),
+            "{mermaid}"
+        );
+    }
+}
@@ -0,0 +1,5 @@
+pub mod c;
+pub mod dot;
+pub mod json;
+pub mod mermaid;
+pub mod smiles;
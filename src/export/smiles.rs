@@ -0,0 +1,306 @@
+// SMILES rendering of molecules and the tape, over the expanded element-only graph.
+// a group's bond to its parent is capped with a hydrogen, like the leading `H` of a literal,
+// and the tape's head has no parent to cap.
+// the backbone of every alkane is its main chain, and everything bonded to it is a branch.
+
+use std::collections::{HashMap, HashSet};
+
+use petgraph::stable_graph::NodeIndex;
+
+use crate::eval::{
+    atom_like::AtomLike,
+    atoms::{Atoms, Expansion},
+    element::Element,
+    functional_groups::{alkane::Alkane, FunctionalGroup},
+    molecule::Molecule,
+};
+
+// the elements that can be written without brackets, with their normal valences
+const ORGANIC: [(&str, &[usize]); 10] = [
+    ("B", &[3]),
+    ("C", &[4]),
+    ("N", &[3, 5]),
+    ("O", &[2]),
+    ("P", &[3, 5]),
+    ("S", &[2, 4, 6]),
+    ("F", &[1]),
+    ("Cl", &[1]),
+    ("Br", &[1]),
+    ("I", &[1]),
+];
+
+struct Smiles<'a> {
+    atoms: &'a Atoms,
+    // the backbone carbons of every alkane, by the path to their alkane,
+    // so chains continue through them instead of branching to them
+    backbones: HashMap<NodeIndex, Vec<NodeIndex>>,
+    visited: HashSet<NodeIndex>,
+    // ring closure digits, by atom
+    rings: HashMap<NodeIndex, Vec<usize>>,
+    children: HashMap<NodeIndex, Vec<NodeIndex>>,
+    // whether the head is bonded to a parent, which is capped with a hydrogen
+    attached: bool,
+}
+
+impl<'a> Smiles<'a> {
+    fn new(
+        expansion: &'a Expansion,
+        root: &Atoms,
+        root_backbone: &[NodeIndex],
+        attached: bool,
+    ) -> Smiles<'a> {
+        let backbones = expansion
+            .origins
+            .iter()
+            .filter(|(_, origin)| match group_at(root, &origin.path) {
+                Some(FunctionalGroup::Alkane(alk)) => alk.backbone().any(|c| *c == origin.idx),
+                Some(_) => false,
+                None => root_backbone.contains(&origin.idx),
+            })
+            .map(|(idx, origin)| (*idx, origin.path.clone()))
+            .collect();
+        Smiles {
+            atoms: &expansion.atoms,
+            backbones,
+            visited: HashSet::new(),
+            rings: HashMap::new(),
+            children: HashMap::new(),
+            attached,
+        }
+    }
+
+    fn write(mut self) -> String {
+        let roots: Vec<NodeIndex> = std::iter::once(self.atoms.head)
+            .chain(self.atoms.atoms().node_indices())
+            .filter(|idx| self.is_atom(*idx))
+            .collect();
+        let mut components = Vec::new();
+        let mut ring = 0;
+        for root in roots {
+            if self.visited.contains(&root) {
+                continue;
+            }
+            self.walk(root, None, &mut ring);
+            let mut out = String::new();
+            self.component(root, &mut out);
+            components.push(out);
+        }
+        components.join(".")
+    }
+
+    // whether the bond from `idx` to `n` is part of the backbone of an alkane
+    fn continues(&self, idx: NodeIndex, n: NodeIndex) -> bool {
+        match (self.backbones.get(&idx), self.backbones.get(&n)) {
+            (Some(l), Some(r)) => l == r,
+            _ => false,
+        }
+    }
+
+    fn is_atom(&self, idx: NodeIndex) -> bool {
+        self.atoms.atoms().contains_node(idx) && !self.atoms.is_filler(idx)
+    }
+
+    // the spanning tree of the component, numbering every bond outside it as a ring closure
+    fn walk(&mut self, idx: NodeIndex, parent: Option<NodeIndex>, ring: &mut usize) {
+        self.visited.insert(idx);
+        let mut neighbors: Vec<NodeIndex> = self.atoms.neighbors(idx).collect();
+        neighbors.sort_by_key(|n| (self.continues(idx, *n), *n));
+        let mut children = Vec::new();
+        for n in neighbors {
+            if Some(n) == parent {
+                continue;
+            }
+            if self.visited.contains(&n) {
+                // only number each ring bond once, from the side that closes it
+                if self.children.contains_key(&n) {
+                    continue;
+                }
+                *ring += 1;
+                self.rings.entry(n).or_default().push(*ring);
+                self.rings.entry(idx).or_default().push(*ring);
+            } else {
+                children.push(n);
+                self.walk(n, Some(idx), ring);
+            }
+        }
+        self.children.insert(idx, children);
+    }
+
+    fn component(&self, idx: NodeIndex, out: &mut String) {
+        out.push_str(&self.atom(idx));
+        for ring in self.rings.get(&idx).into_iter().flatten() {
+            match ring {
+                1..=9 => out.push_str(&ring.to_string()),
+                _ => out.push_str(&format!("%{ring}")),
+            }
+        }
+        let children = &self.children[&idx];
+        for (i, child) in children.iter().enumerate() {
+            if i + 1 < children.len() {
+                out.push('(');
+                self.component(*child, out);
+                out.push(')');
+            } else {
+                self.component(*child, out);
+            }
+        }
+    }
+
+    fn atom(&self, idx: NodeIndex) -> String {
+        let Some(Molecule::E(e)) = self.atoms.atoms().node_weight(idx) else {
+            unreachable!("expanded molecules only hold elements")
+        };
+        let degree = self.atoms.neighbors(idx).count();
+        let fillers = self.atoms.atoms().neighbors(idx).count() - degree;
+        let cap = if self.attached && idx == self.atoms.head {
+            1
+        } else {
+            0
+        };
+        let hydrogens = self.atoms.implicit_hydrogens(idx, self.attached) + fillers + cap;
+        atom(e, degree, hydrogens)
+    }
+}
+
+// an atom with `degree` bonds to other written atoms and `hydrogens` hydrogens
+fn atom(e: &Element, degree: usize, hydrogens: usize) -> String {
+    // antimatter has no SMILES equivalent, so it is written as matter with atom class 1
+    let (matter, class) = match e {
+        Element::Anti(e) => (e.as_ref(), ":1"),
+        e => (e, ""),
+    };
+    let symbol = matter.to_string();
+    if class.is_empty() {
+        if let Some((_, valences)) = ORGANIC.iter().find(|(s, _)| *s == symbol) {
+            let default = valences
+                .iter()
+                .find(|v| **v >= degree)
+                .map_or(0, |v| v - degree);
+            if default == hydrogens {
+                return symbol;
+            }
+        }
+    }
+    let hydrogens = match hydrogens {
+        0 => String::new(),
+        1 => String::from("H"),
+        n => format!("H{n}"),
+    };
+    format!("[{symbol}{hydrogens}{class}]")
+}
+
+// the group that a path of an expansion leads to, or `None` for the outermost molecule
+fn group_at<'a>(root: &'a Atoms, path: &[NodeIndex]) -> Option<&'a FunctionalGroup> {
+    let (last, path) = path.split_last()?;
+    let atoms = path
+        .iter()
+        .fold(root, |atoms, idx| match atoms.atoms().node_weight(*idx) {
+            Some(Molecule::F(fg)) => fg.get_atoms(),
+            _ => unreachable!("expansion paths only go through functional groups"),
+        });
+    match atoms.atoms().node_weight(*last) {
+        Some(Molecule::F(fg)) => Some(fg),
+        _ => unreachable!("expansion paths only go through functional groups"),
+    }
+}
+
+impl Atoms {
+    // the molecule as it is written after the leading `H` of a literal
+    pub fn to_smiles(&self) -> String {
+        Smiles::new(&self.expand(), self, &[], true).write()
+    }
+}
+
+impl FunctionalGroup {
+    pub fn to_smiles(&self) -> String {
+        match self {
+            FunctionalGroup::Alkane(alk) => alk.smiles(true),
+            fg => fg.get_atoms().to_smiles(),
+        }
+    }
+}
+
+impl Alkane {
+    // the alkane as the tape, which isn't bonded to anything
+    pub fn to_smiles(&self) -> String {
+        self.smiles(false)
+    }
+
+    fn smiles(&self, attached: bool) -> String {
+        // branches are written in index order
+        let alk = self.in_bond_order();
+        let backbone: Vec<NodeIndex> = alk.backbone().copied().collect();
+        Smiles::new(
+            &alk.get_atoms().expand(),
+            alk.get_atoms(),
+            &backbone,
+            attached,
+        )
+        .write()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::value::Value;
+
+    fn smiles(v: Value) -> String {
+        FunctionalGroup::from(v).to_smiles()
+    }
+
+    #[test]
+    fn writes_values() {
+        assert_eq!(smiles(Value::from(0)), "O");
+        assert_eq!(smiles(Value::from(3)), "O[Li]");
+        assert_eq!(smiles(Value::Boolean(true)), "B(O[H])[H]");
+        assert_eq!(
+            smiles(Value::List(vec![Value::from(1), Value::from(2)])),
+            "C(O[H])C(O[He])C"
+        );
+    }
+
+    #[test]
+    fn brackets_heavy_elements_and_antimatter() {
+        assert_eq!(smiles(Value::from(119)), "O[Uue]");
+        // antimatter is matter with atom class 1
+        assert!(smiles(Value::from(-26)).ends_with(":1]"));
+    }
+
+    #[test]
+    fn values_round_trip() {
+        let values = [
+            Value::from(0),
+            Value::from(3),
+            Value::from(119),
+            Value::from(-26),
+            Value::Boolean(false),
+            Value::String(vec!['h', 'i']),
+            Value::List(vec![Value::from(1), Value::from(2)]),
+            Value::Map(
+                [(Value::from(1), Value::Boolean(false))]
+                    .into_iter()
+                    .collect(),
+            ),
+        ];
+        for v in values {
+            let fg = FunctionalGroup::from(v.clone());
+            let read = FunctionalGroup::from_smiles(&fg.to_smiles()).unwrap();
+            assert_eq!(read, fg, "{v}");
+        }
+    }
+
+    #[test]
+    fn the_tape_has_no_parent_to_cap() {
+        let mut tape = Alkane::new();
+        assert_eq!(tape.to_smiles(), "C");
+        tape.add_functional_group(FunctionalGroup::from(Value::from(3)));
+        tape.add_carbon_after();
+        assert_eq!(tape.to_smiles(), "C(O[Li])C");
+    }
+
+    #[test]
+    fn closes_rings() {
+        assert_eq!(Atoms::from_smiles("C1CC1").unwrap().to_smiles(), "C1CC1");
+    }
+}
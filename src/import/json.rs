@@ -0,0 +1,465 @@
+// reading the JSON written by `export::json` back into values and molecules.
+
+use std::{collections::VecDeque, fmt::Display, str::FromStr};
+
+use indexmap::IndexMap;
+use petgraph::stable_graph::NodeIndex;
+use serde_json::{Map, Value as Json};
+
+use crate::eval::{
+    atom_like::AtomLike,
+    atoms::Atoms,
+    element::{Element, ElementError},
+    functional_groups::{
+        alkane::{Alkane, Container},
+        amine::Amine,
+        borinic::BorinicAcid,
+        classify::ClassifyError,
+        ester::Ester,
+        ether::Ether,
+        sulfide::Sulfide,
+        FunctionalGroup,
+    },
+    molecule::Molecule,
+    number::{self, Number},
+    value::{Value, ValueError},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonError {
+    // not JSON at all
+    Syntax(String),
+    // JSON that doesn't describe a value or a molecule
+    Shape {
+        expected: &'static str,
+        got: &'static str,
+    },
+    Element(ElementError),
+    Value(ValueError),
+    // a functional group whose atoms aren't shaped like its kind
+    Malformed(ClassifyError),
+}
+
+impl Display for JsonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JsonError::Syntax(err) => write!(f, "invalid JSON: {err}"),
+            JsonError::Shape { expected, got } => write!(f, "expected {expected}, got {got}"),
+            JsonError::Element(err) => write!(f, "{err}"),
+            JsonError::Value(err) => write!(f, "{err}"),
+            JsonError::Malformed(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+pub(crate) fn shape(expected: &'static str, got: &Json) -> JsonError {
+    let got = match got {
+        Json::Null => "null",
+        Json::Bool(_) => "a boolean",
+        Json::Number(_) => "a number",
+        Json::String(_) => "a string",
+        Json::Array(_) => "an array",
+        Json::Object(_) => "an object",
+    };
+    JsonError::Shape { expected, got }
+}
+
+// the one key and value of a tagged object like `{"pair": [l, r]}`
+fn tagged(object: &Map<String, Json>) -> Option<(&str, &Json)> {
+    match object.iter().collect::<Vec<_>>()[..] {
+        [(tag, json)] => Some((tag.as_str(), json)),
+        _ => None,
+    }
+}
+
+fn two(json: &Json) -> Result<[&Json; 2], JsonError> {
+    match json {
+        Json::Array(v) if v.len() == 2 => Ok([&v[0], &v[1]]),
+        json => Err(shape("an array of 2", json)),
+    }
+}
+
+fn number(json: &Json) -> Result<Number, JsonError> {
+    let digits = match json {
+        Json::Number(n) if n.is_f64() => return Err(shape("an integer", json)),
+        Json::Number(n) => n.to_string(),
+        Json::Object(object) => match tagged(object) {
+            Some(("number", Json::String(digits))) => digits.to_string(),
+            _ => return Err(shape("an integer", json)),
+        },
+        json => return Err(shape("an integer", json)),
+    };
+    number::parse(&digits).ok_or(JsonError::Value(ValueError::Overflow(digits)))
+}
+
+impl Value {
+    pub fn from_json(json: &Json) -> Result<Value, JsonError> {
+        match json {
+            Json::Bool(b) => Ok(Value::Boolean(*b)),
+            Json::Number(_) => Ok(Value::from(number(json)?)),
+            Json::String(s) => Ok(Value::String(s.chars().collect())),
+            Json::Array(v) => Ok(Value::List(
+                v.iter().map(Value::from_json).collect::<Result<_, _>>()?,
+            )),
+            Json::Object(object) => match tagged(object) {
+                Some(("number", _)) => Ok(Value::from(number(json)?)),
+                Some(("rational", v)) => {
+                    let [n, d] = two(v)?;
+                    Value::from(number(n)?)
+                        .checked_div(Value::from(number(d)?))
+                        .map_err(JsonError::Value)
+                }
+                Some(("pair", v)) => {
+                    let [l, r] = two(v)?;
+                    Ok(Value::Pair(
+                        Box::new(Value::from_json(l)?),
+                        Box::new(Value::from_json(r)?),
+                    ))
+                }
+                Some(("map", Json::Array(entries))) => {
+                    let mut map = IndexMap::new();
+                    for entry in entries {
+                        let [k, v] = two(entry)?;
+                        map.insert(Value::from_json(k)?, Value::from_json(v)?);
+                    }
+                    Ok(Value::Map(map))
+                }
+                _ => Err(shape(
+                    "a number, rational, pair or map object with a single key",
+                    json,
+                )),
+            },
+            Json::Null => Err(shape("a value", json)),
+        }
+    }
+}
+
+impl Atoms {
+    pub fn from_json(json: &Json) -> Result<Atoms, JsonError> {
+        let Json::Object(fields) = json else {
+            return Err(shape("a molecule object", json));
+        };
+        Ok(Atoms::from_json_fields(fields)?.0)
+    }
+
+    // the molecule, and the index each written position ended up at
+    fn from_json_fields(fields: &Map<String, Json>) -> Result<(Atoms, Vec<NodeIndex>), JsonError> {
+        let mut atoms = Atoms::new();
+        let written = fields.get("atoms").unwrap_or(&Json::Null);
+        let Json::Array(written) = written else {
+            return Err(shape("an array of atoms", written));
+        };
+        let mut indices = Vec::new();
+        for atom in written {
+            let m = match atom {
+                Json::String(symbol) => {
+                    Molecule::E(Element::from_str(symbol).map_err(JsonError::Element)?)
+                }
+                Json::Object(_) => Molecule::F(FunctionalGroup::from_json(atom)?),
+                atom => return Err(shape("an element symbol or a functional group", atom)),
+            };
+            indices.push(atoms.add_node(m));
+        }
+        let bonds = fields.get("bonds").unwrap_or(&Json::Null);
+        let Json::Array(bonds) = bonds else {
+            return Err(shape("an array of bonds", bonds));
+        };
+        for bond in bonds {
+            let [l, r] = two(bond)?;
+            atoms.add_edge(index(&indices, l)?, index(&indices, r)?);
+        }
+        if let Some(head) = fields.get("head") {
+            atoms.head = index(&indices, head)?;
+        }
+        if let Some(fillers) = fields.get("fillers") {
+            let Json::Array(fillers) = fillers else {
+                return Err(shape("an array of atom positions", fillers));
+            };
+            for filler in fillers {
+                atoms.set_filler(index(&indices, filler)?);
+            }
+        }
+        Ok((atoms, indices))
+    }
+}
+
+// the index of the atom written at a position
+fn index(indices: &[NodeIndex], position: &Json) -> Result<NodeIndex, JsonError> {
+    position
+        .as_u64()
+        .and_then(|p| indices.get(p as usize))
+        .copied()
+        .ok_or_else(|| shape("the position of an atom", position))
+}
+
+impl FunctionalGroup {
+    pub fn from_json(json: &Json) -> Result<FunctionalGroup, JsonError> {
+        let Json::Object(fields) = json else {
+            return Err(shape("a functional group object", json));
+        };
+        let (atoms, indices) = Atoms::from_json_fields(fields)?;
+        let field = |name: &str| fields.get(name).unwrap_or(&Json::Null);
+        let kind = field("kind");
+        let fg = match kind.as_str() {
+            Some("Ether") => FunctionalGroup::Ether(Ether(atoms)),
+            Some("BorinicAcid") => FunctionalGroup::BorinicAcid(BorinicAcid(atoms)),
+            Some("Sulfide") => FunctionalGroup::Sulfide(Sulfide(atoms)),
+            Some("Amine") => FunctionalGroup::Amine(Amine(atoms)),
+            Some("Ester") => {
+                let carbonyl = index(&indices, field("carbonyl"))?;
+                let alkoxy = index(&indices, field("alkoxy"))?;
+                FunctionalGroup::Ester(Ester::from_parts(atoms, carbonyl, alkoxy))
+            }
+            Some("Alkane") => {
+                let backbone = field("backbone");
+                let Json::Array(backbone) = backbone else {
+                    return Err(shape("an array of atom positions", backbone));
+                };
+                let backbone = backbone
+                    .iter()
+                    .map(|c| index(&indices, c))
+                    .collect::<Result<VecDeque<_>, _>>()?;
+                let current_atom = index(&indices, field("current"))?;
+                let marker = match fields.get("marker") {
+                    Some(marker) => Some(index(&indices, marker)?),
+                    None => None,
+                };
+                FunctionalGroup::Alkane(Alkane::from_parts(atoms, current_atom, backbone, marker))
+            }
+            _ => {
+                return Err(shape(
+                    "a kind of `Ether`, `Ester`, `BorinicAcid`, `Sulfide`, `Amine` or `Alkane`",
+                    kind,
+                ))
+            }
+        };
+        check(&fg)?;
+        Ok(fg)
+    }
+}
+
+// that a group read back is shaped like its kind, so getting its value can't panic.
+// the classifier recognises the shape, and the rest is what it leaves to the groups it builds,
+// like which atoms are already groups, and the atoms an ester or alkane keeps track of.
+fn check(fg: &FunctionalGroup) -> Result<(), JsonError> {
+    let atoms = fg.get_atoms();
+    let malformed = || {
+        JsonError::Malformed(ClassifyError::Malformed {
+            kind: fg.kind(),
+            structure: atoms.to_smiles(),
+        })
+    };
+    let classified = FunctionalGroup::try_from(atoms).map_err(|err| match err {
+        ClassifyError::Value(err) => JsonError::Value(err),
+        err => JsonError::Malformed(err),
+    })?;
+    if classified.kind() != fg.kind() {
+        return Err(malformed());
+    }
+    let is_group = |idx: NodeIndex| matches!(atoms.atoms()[idx], Molecule::F(_));
+    let is = |idx: NodeIndex, e: Element| atoms.atoms()[idx] == Molecule::E(e);
+    let well_formed = match fg {
+        FunctionalGroup::Ether(_) | FunctionalGroup::BorinicAcid(_) => true,
+        FunctionalGroup::Sulfide(_) | FunctionalGroup::Amine(_) => {
+            atoms.neighbors(atoms.head).all(is_group)
+        }
+        FunctionalGroup::Ester(est) => {
+            let bonded = |idx: NodeIndex| atoms.atoms().find_edge(atoms.head, idx).is_some();
+            [est.carbonyl(), est.alkoxy()]
+                .into_iter()
+                .all(|o| is(o, Element::O) && bonded(o))
+                && est.denominator().is_ok_and(|d| d != Number::default())
+        }
+        FunctionalGroup::Alkane(alk) => {
+            let backbone: Vec<NodeIndex> = alk.backbone().copied().collect();
+            let chained = backbone.first() == Some(&atoms.head)
+                && backbone.iter().all(|c| is(*c, Element::C))
+                && backbone
+                    .windows(2)
+                    .all(|pair| atoms.atoms().find_edge(pair[0], pair[1]).is_some());
+            let marked = match alk.marker() {
+                Some(f) => is(f, Element::F) && atoms.atoms().find_edge(atoms.head, f).is_some(),
+                None => true,
+            };
+            let held = backbone.iter().all(|c| {
+                alk.get_bonded_molecules(*c).iter().all(|m| {
+                    matches!(
+                        (m, alk.container()),
+                        (Molecule::F(FunctionalGroup::Amine(_)), _)
+                            | (Molecule::F(_), Container::List)
+                    )
+                })
+            });
+            chained && marked && held && backbone.contains(&alk.current_atom)
+        }
+    };
+    match well_formed {
+        true => Ok(()),
+        false => Err(malformed()),
+    }
+}
+
+// groups to bond to the tape, from a JSON array of values or functional groups.
+// objects with a `kind` are functional groups, everything else is a value.
+pub fn groups_from_json(source: &str) -> Result<Vec<FunctionalGroup>, JsonError> {
+    let json: Json =
+        serde_json::from_str(source).map_err(|err| JsonError::Syntax(err.to_string()))?;
+    let Json::Array(items) = json else {
+        return Err(shape("an array of values or functional groups", &json));
+    };
+    items
+        .iter()
+        .map(|item| match item {
+            Json::Object(fields) if fields.contains_key("kind") => FunctionalGroup::from_json(item),
+            item => Value::from_json(item).map(FunctionalGroup::from),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::eval::traits::Valuable;
+
+    fn group(json: Json) -> Result<FunctionalGroup, JsonError> {
+        FunctionalGroup::from_json(&json)
+    }
+
+    fn is_malformed(json: Json) -> bool {
+        matches!(group(json), Err(JsonError::Malformed(_)))
+    }
+
+    #[test]
+    fn groups_round_trip() {
+        let values = json!([
+            3,
+            -3,
+            { "rational": [1, 3] },
+            true,
+            "hi",
+            "",
+            { "pair": [1, "x"] },
+            [1, 2, [3]],
+            { "map": [[1, 2], ["a", [true]]] },
+        ]);
+        for value in values.as_array().unwrap() {
+            let value = Value::from_json(value).unwrap();
+            let fg = FunctionalGroup::from(value.clone());
+            assert_eq!(group(fg.to_json()).unwrap().value(), value);
+        }
+    }
+
+    #[test]
+    fn tapes_round_trip_in_bond_order() {
+        let mut tape = Alkane::new();
+        for n in 1..=3 {
+            tape.add_functional_group(FunctionalGroup::from(Value::from(n)));
+        }
+        tape.take_bonded_groups(2).unwrap();
+        for n in 4..=5 {
+            tape.add_functional_group(FunctionalGroup::from(Value::from(n)));
+        }
+        let read = group(FunctionalGroup::Alkane(tape).to_json()).unwrap();
+        assert_eq!(
+            read.value(),
+            Value::List([1, 4, 5].map(Value::from).to_vec())
+        );
+    }
+
+    #[test]
+    fn amines_need_two_groups() {
+        assert!(is_malformed(
+            json!({ "kind": "Amine", "atoms": ["N"], "bonds": [], "head": 0 })
+        ));
+        assert!(is_malformed(json!({
+            "kind": "Amine",
+            "atoms": ["N", "O", "Li", "O", "Be"],
+            "bonds": [[0, 1], [1, 2], [0, 3], [3, 4]],
+            "head": 0,
+        })));
+    }
+
+    #[test]
+    fn esters_need_a_denominator() {
+        // `Li` and `-Li` weigh 0 together
+        assert!(is_malformed(json!({
+            "kind": "Ester",
+            "atoms": ["C", "O", "O", "Li", "-Li"],
+            "bonds": [[0, 1], [0, 2], [2, 3], [2, 4]],
+            "head": 0,
+            "carbonyl": 1,
+            "alkoxy": 2,
+        })));
+        // the carbonyl and alkoxy have to be the oxygens on the carbon
+        assert!(is_malformed(json!({
+            "kind": "Ester",
+            "atoms": ["C", "O", "O", "Be"],
+            "bonds": [[0, 1], [0, 2], [2, 3]],
+            "head": 0,
+            "carbonyl": 3,
+            "alkoxy": 2,
+        })));
+    }
+
+    #[test]
+    fn sulfides_hold_text() {
+        assert!(is_malformed(json!({
+            "kind": "Sulfide",
+            "atoms": ["S", "N"],
+            "bonds": [[0, 1]],
+            "head": 0,
+        })));
+        // an oxygen that isn't an ether group yet
+        assert!(is_malformed(json!({
+            "kind": "Sulfide",
+            "atoms": ["S", "O", "Li"],
+            "bonds": [[0, 1], [1, 2]],
+            "head": 0,
+        })));
+    }
+
+    #[test]
+    fn maps_hold_pairs() {
+        let ether = FunctionalGroup::from(Value::from(3)).to_json();
+        assert!(is_malformed(json!({
+            "kind": "Alkane",
+            "atoms": ["C", "F", ether],
+            "bonds": [[0, 1], [0, 2]],
+            "head": 0,
+            "backbone": [0],
+            "current": 0,
+            "marker": 1,
+        })));
+    }
+
+    #[test]
+    fn alkanes_need_a_backbone_of_carbons() {
+        assert!(is_malformed(json!({
+            "kind": "Alkane",
+            "atoms": ["C", "C"],
+            "bonds": [[0, 1]],
+            "head": 0,
+            "backbone": [1, 0],
+            "current": 0,
+        })));
+        assert!(is_malformed(json!({
+            "kind": "Alkane",
+            "atoms": ["C", "Li"],
+            "bonds": [[0, 1]],
+            "head": 0,
+            "backbone": [0],
+            "current": 0,
+        })));
+    }
+
+    #[test]
+    fn kinds_have_to_match_their_shape() {
+        let ether = FunctionalGroup::from(Value::from(3)).to_json();
+        let mut sulfide = ether.clone();
+        sulfide["kind"] = json!("Sulfide");
+        assert!(group(ether).is_ok());
+        assert!(is_malformed(sulfide));
+    }
+}
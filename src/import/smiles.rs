@@ -0,0 +1,242 @@
+// reading SMILES into molecules, the reverse of `export::smiles`.
+// molecules here only have single bonds between neutral atoms, so anything else is unsupported.
+// hydrogen counts are ignored, since every atom is saturated with filler hydrogens anyway,
+// and atom class 1 marks antimatter, like when exporting.
+
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+
+use petgraph::stable_graph::NodeIndex;
+
+use crate::eval::{
+    atom_like::AtomLike,
+    atoms::Atoms,
+    element::{Element, ElementError},
+    functional_groups::{classify::ClassifyError, FunctionalGroup},
+    molecule::Molecule,
+};
+
+// the elements that can be written without brackets, longest symbols first
+const ORGANIC: [&str; 10] = ["Cl", "Br", "B", "C", "N", "O", "P", "S", "F", "I"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SmilesError {
+    // malformed SMILES, at a character index
+    Syntax { at: usize, reason: &'static str },
+    // valid SMILES that means something molecules here can't have
+    Unsupported { at: usize, feature: &'static str },
+    Element { at: usize, err: ElementError },
+    // a molecule that isn't shaped like any functional group
+    Unrecognised(ClassifyError),
+}
+
+impl Display for SmilesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SmilesError::Syntax { at, reason } => write!(f, "invalid SMILES at {at}: {reason}"),
+            SmilesError::Unsupported { at, feature } => {
+                write!(f, "unsupported SMILES at {at}: {feature}")
+            }
+            SmilesError::Element { at, err } => write!(f, "invalid SMILES at {at}: {err}"),
+            SmilesError::Unrecognised(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+struct Reader {
+    chars: Vec<char>,
+    idx: usize,
+    atoms: Atoms,
+    // the atom the next one bonds to
+    previous: Option<NodeIndex>,
+    // where to go back to at the end of each open branch
+    branches: Vec<Option<NodeIndex>>,
+    // open ring bonds, by their number
+    rings: HashMap<usize, NodeIndex>,
+}
+
+impl Reader {
+    fn new(smiles: &str) -> Reader {
+        Reader {
+            chars: smiles.chars().collect(),
+            idx: 0,
+            atoms: Atoms::new(),
+            previous: None,
+            branches: Vec::new(),
+            rings: HashMap::new(),
+        }
+    }
+
+    fn read(mut self) -> Result<Atoms, SmilesError> {
+        while let Some(c) = self.peek() {
+            match c {
+                'A'..='Z' => self.organic()?,
+                'a'..='z' => return Err(self.unsupported("aromatic atoms")),
+                '[' => self.bracket()?,
+                '(' => {
+                    if self.previous.is_none() {
+                        return Err(self.syntax("a branch has to follow an atom"));
+                    }
+                    self.branches.push(self.previous);
+                    self.idx += 1;
+                }
+                ')' => {
+                    let Some(previous) = self.branches.pop() else {
+                        return Err(self.syntax("unopened branch"));
+                    };
+                    self.previous = previous;
+                    self.idx += 1;
+                }
+                // an explicit single bond is the same as no bond symbol
+                '-' => self.idx += 1,
+                '=' | '#' | '$' | ':' => return Err(self.unsupported("bonds other than single")),
+                '/' | '\\' => return Err(self.unsupported("double bond stereochemistry")),
+                '.' => {
+                    self.previous = None;
+                    self.idx += 1;
+                }
+                '0'..='9' => {
+                    let ring = self.digits(1)?;
+                    self.ring(ring)?;
+                }
+                '%' => {
+                    self.idx += 1;
+                    let ring = self.digits(2)?;
+                    self.ring(ring)?;
+                }
+                _ => return Err(self.syntax("unknown character")),
+            }
+        }
+        if !self.branches.is_empty() {
+            return Err(self.syntax("unclosed branch"));
+        }
+        if !self.rings.is_empty() {
+            return Err(self.syntax("unclosed ring bond"));
+        }
+        Ok(self.atoms)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.idx).copied()
+    }
+
+    fn syntax(&self, reason: &'static str) -> SmilesError {
+        SmilesError::Syntax {
+            at: self.idx,
+            reason,
+        }
+    }
+
+    fn unsupported(&self, feature: &'static str) -> SmilesError {
+        SmilesError::Unsupported {
+            at: self.idx,
+            feature,
+        }
+    }
+
+    fn add_atom(&mut self, e: Element) {
+        let atom = self.atoms.add_node(Molecule::E(e));
+        if let Some(previous) = self.previous {
+            self.atoms.add_edge(previous, atom);
+        }
+        self.previous = Some(atom);
+    }
+
+    fn organic(&mut self) -> Result<(), SmilesError> {
+        let rest: String = self.chars[self.idx..].iter().take(2).collect();
+        let Some(symbol) = ORGANIC.iter().find(|symbol| rest.starts_with(**symbol)) else {
+            return Err(self.syntax("only organic atoms can be written without brackets"));
+        };
+        let e = Element::from_str(symbol).unwrap();
+        self.idx += symbol.len();
+        self.add_atom(e);
+        Ok(())
+    }
+
+    // `[symbol Hcount :class]`
+    fn bracket(&mut self) -> Result<(), SmilesError> {
+        self.idx += 1;
+        match self.peek() {
+            Some('0'..='9') => return Err(self.unsupported("isotopes")),
+            Some('a'..='z') => return Err(self.unsupported("aromatic atoms")),
+            Some('A'..='Z') => {}
+            _ => return Err(self.syntax("expected an element symbol")),
+        }
+        let start = self.idx;
+        self.idx += 1;
+        while let Some('a'..='z') = self.peek() {
+            self.idx += 1;
+        }
+        let symbol: String = self.chars[start..self.idx].iter().collect();
+        let mut e =
+            Element::from_str(&symbol).map_err(|err| SmilesError::Element { at: start, err })?;
+        if let Some('@') = self.peek() {
+            return Err(self.unsupported("chirality"));
+        }
+        if let Some('H') = self.peek() {
+            self.idx += 1;
+            while let Some('0'..='9') = self.peek() {
+                self.idx += 1;
+            }
+        }
+        if let Some('+' | '-') = self.peek() {
+            return Err(self.unsupported("charges"));
+        }
+        if let Some(':') = self.peek() {
+            self.idx += 1;
+            let class_at = self.idx;
+            while let Some('0'..='9') = self.peek() {
+                self.idx += 1;
+            }
+            let class: String = self.chars[class_at..self.idx].iter().collect();
+            if class != "1" {
+                return Err(self.unsupported("atom classes other than 1"));
+            }
+            e = -e;
+        }
+        if self.peek() != Some(']') {
+            return Err(self.syntax("expected `]`"));
+        }
+        self.idx += 1;
+        self.add_atom(e);
+        Ok(())
+    }
+
+    fn digits(&mut self, n: usize) -> Result<usize, SmilesError> {
+        let digits: String = self.chars[self.idx..].iter().take(n).collect();
+        if digits.len() < n || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(self.syntax("expected a ring bond number"));
+        }
+        self.idx += n;
+        Ok(digits.parse().unwrap())
+    }
+
+    // opens a ring bond, or closes it if it is already open
+    fn ring(&mut self, ring: usize) -> Result<(), SmilesError> {
+        let Some(previous) = self.previous else {
+            return Err(self.syntax("a ring bond has to follow an atom"));
+        };
+        match self.rings.remove(&ring) {
+            Some(other) => {
+                self.atoms.add_edge(other, previous);
+            }
+            None => {
+                self.rings.insert(ring, previous);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Atoms {
+    // the first atom written is the head
+    pub fn from_smiles(smiles: &str) -> Result<Atoms, SmilesError> {
+        Reader::new(smiles).read()
+    }
+}
+
+impl FunctionalGroup {
+    pub fn from_smiles(smiles: &str) -> Result<FunctionalGroup, SmilesError> {
+        let atoms = Atoms::from_smiles(smiles)?;
+        FunctionalGroup::try_from(&atoms).map_err(SmilesError::Unrecognised)
+    }
+}
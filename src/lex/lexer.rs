@@ -60,13 +60,10 @@ impl Lexer {
         let tokens: Vec<Token> = tokens
             .iter()
             // no comments or spaces
-            .filter(|tok| match tok.token {
-                Type::Comment(_) | Type::Space => false,
-                _ => true,
-            })
+            .filter(|tok| !matches!(tok.token, Type::Comment(_) | Type::Space))
             // remove duplicate newlines from removing comments
             .dedup_by(|l, r| l.token == r.token)
-            .map(|tok| tok.clone())
+            .cloned()
             .collect();
         // remove "empty" lines (newlines at the start of the token list)
         let first = tokens.first();
@@ -99,15 +96,27 @@ impl Lexer {
             ':' => ok_token!(self, Type::Colon),
             ',' => ok_token!(self, Type::Comma),
             '^' => ok_token!(self, Type::Caret),
+            '/' => ok_token!(self, Type::Slash),
+            '$' => ok_token!(self, Type::Dollar),
             '-' => {
                 start_vars!(self, idx, line, col);
                 self.read_char()?;
-                if self.ch.is_digit(10) {
+                if self.ch.is_ascii_digit() {
                     self.put_back()?; // digit
                     self.put_back()?; // minus
                     self.number()
+                } else if self.ch.is_ascii_uppercase() {
+                    // antimatter element
+                    let mut token = self.element()?;
+                    if let Type::Element(symbol) = token.token {
+                        token.token = Type::Element(format!("-{symbol}"));
+                    }
+                    token.loc.start = Indexes(idx, line, col);
+                    Ok(token)
                 } else if self.ch != '>' {
-                    Err(format!("expected `>` after `-`, got {}", self.ch))
+                    // an arrow, a negative number or antimatter, so `--Fe` can't be antimatter twice
+                    let message = format!("expected `>` after `-`, got {}", self.ch);
+                    ok_token!(self, Type::Invalid(message), idx, line, col)
                 } else {
                     ok_token!(Type::Arrow, idx, line, col)
                 }
@@ -119,7 +128,7 @@ impl Lexer {
             // string literal
             '\"' => self.string(),
             // number literal
-            c if c.is_digit(10) => {
+            c if c.is_ascii_digit() => {
                 self.put_back()?;
                 self.number()
             }
@@ -155,9 +164,10 @@ impl Lexer {
         // begin digits
         if self.ch == '-' {
             chars.push(self.ch);
+            self.read_char()?;
         }
         self.read_char()?;
-        while self.ch.is_digit(10) {
+        while self.ch.is_ascii_digit() {
             chars.push(self.ch);
             self.read_char()?;
         }
@@ -192,9 +202,9 @@ impl Lexer {
         lexeme.push(self.ch);
         self.read_char()?;
         while self.ch.is_ascii_lowercase() {
-            if lexeme == String::from("light") {
+            if lexeme == "light" {
                 return ok_token!(self, Type::Light, idx, line, col);
-            } else if lexeme == String::from("heat") {
+            } else if lexeme == "heat" {
                 return ok_token!(self, Type::Heat, idx, line, col);
             } else {
                 lexeme.push(self.ch);
@@ -258,3 +268,41 @@ use ok_token;
 use start_vars;
 
 use super::tok::{Token, Type};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn types(source: &str) -> Vec<Type> {
+        Lexer::new(source.to_string())
+            .all_tokens()
+            .into_iter()
+            .map(|tok| tok.token)
+            .collect()
+    }
+
+    #[test]
+    fn lexes_antimatter_as_one_element() {
+        assert_eq!(
+            types("HO-Fe -> H\n"),
+            [
+                Type::Element(String::from("H")),
+                Type::Element(String::from("O")),
+                Type::Element(String::from("-Fe")),
+                Type::Arrow,
+                Type::Element(String::from("H")),
+                Type::Newline,
+            ]
+        );
+        assert_eq!(types("-Uue\n")[0], Type::Element(String::from("-Uue")));
+        assert_eq!(types("H^-3\n")[2], Type::Number(String::from("-3")));
+    }
+
+    #[test]
+    fn keeps_a_stray_minus_for_the_parser_to_reject() {
+        let types = types("HO--Fe -> H\n");
+        assert!(matches!(&types[2], Type::Invalid(_)), "{types:?}");
+        // the second `-` went with the first, so `Fe` is matter
+        assert_eq!(types[3], Type::Element(String::from("Fe")));
+    }
+}
@@ -1,7 +1,8 @@
 use std::fmt::Display;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub enum Type {
+    #[default]
     None,
     Comment(String),
     // whitespace
@@ -22,6 +23,8 @@ pub enum Type {
     Arrow,
     Comma,
     Caret,
+    Slash,
+    Dollar,
     // literals
     Number(String),
     String(String),
@@ -29,6 +32,8 @@ pub enum Type {
     Light,
     Heat,
     Element(String),
+    // something the lexer couldn't read, and why, left for the parser to reject
+    Invalid(String),
 }
 
 impl Display for Type {
@@ -37,12 +42,6 @@ impl Display for Type {
     }
 }
 
-impl Default for Type {
-    fn default() -> Self {
-        Self::None
-    }
-}
-
 #[derive(Debug, Clone, Default)]
 pub struct Token {
     pub token: Type,
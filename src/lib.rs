@@ -0,0 +1,65 @@
+//! Esoteric Reaction as a library, for running programs from Rust.
+//!
+//! [`run()`] runs a program once, writing what it prints to `out`.
+//! To keep the tape between programs, or to look at it afterwards, make an [`Interpreter`],
+//! and [`Valuable`] gets the value of the groups on it:
+//!
+//! ```
+//! use esoteric_reaction::{Interpreter, Valuable, Value};
+//!
+//! let mut out = Vec::new();
+//! let mut interpreter = Interpreter::new(&mut out);
+//! interpreter.run("H^3Xn -> H\n")?;
+//! let values: Vec<Value> = interpreter.bonded_groups().iter().map(|fg| fg.value()).collect();
+//! assert_eq!(values, [Value::from(3)]);
+//! drop(interpreter);
+//! assert_eq!(out, b"3\n");
+//! # Ok::<(), esoteric_reaction::Error>(())
+//! ```
+//!
+//! The re-exports below are the stable API. The modules are public so their types can be named,
+//! like the `ParseTree` from [`Parser::parse`], but anything only reachable through them may change.
+
+#[cfg(feature = "capi")]
+pub mod capi;
+pub mod error;
+pub mod eval;
+pub mod export;
+pub mod import;
+pub mod lex;
+pub mod par;
+pub mod run;
+
+use std::io::Write;
+
+pub use error::Error;
+pub use eval::{
+    element::Element,
+    functional_groups::{
+        alkane::Alkane, amine::Amine, borinic::BorinicAcid, ester::Ester, ether::Ether,
+        sulfide::Sulfide, FunctionalGroup,
+    },
+    traits::{Valuable, Weighable},
+    value::Value,
+};
+pub use lex::lexer::Lexer;
+pub use par::parser::Parser;
+pub use run::{
+    host::BindError,
+    interpreter::Interpreter,
+    runtime_error::RuntimeError,
+    snapshot::Snapshot,
+    tape::{CompactTape, Tape},
+};
+
+/// Runs a program on a fresh tape, writing what it prints to `out`.
+///
+/// ```
+/// let mut out = Vec::new();
+/// esoteric_reaction::run("H^1 + H^2Xn -> H\n", &mut out)?;
+/// assert_eq!(out, b"1\n2\n");
+/// # Ok::<(), esoteric_reaction::Error>(())
+/// ```
+pub fn run<W: Write>(source: &str, out: W) -> Result<(), Error> {
+    Interpreter::new(out).run(source)
+}
@@ -1,45 +1,126 @@
-mod eval;
-mod lex;
-mod par;
 mod util;
 
-use lex::lexer::Lexer;
-use par::parser::Parser;
-use util::args::parse_args;
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Stdout},
+    process,
+};
 
-fn main() {
-    // let code = r"C_3H_8+5O_2 -> 3CO_2+4H_2O".to_string();
-    // let mut lexer = lex::Lexer::new(code.to_string());
-
-    // let tokens = lexer.all_tokens();
-    // println!("{:?}", tokens);
-
-    // let mut alk = Alkane::new();
-    // println!("{:?}", &alk);
-
-    // let mut tree: Tree<Atom> = Tree::new();
-
-    // let three = Ether::from(3);
-    // let ten = Ether::from(10);
-    // eprintln!("3={} 10={}", three.value(), ten.value());
-    // let thirteen = three - ten;
+use esoteric_reaction::{
+    import, run::bytecode, CompactTape, Error, Interpreter, Lexer, Parser, Tape,
+};
+use util::args::{parse_args, Build, Options, Target, Task};
 
-    let source = parse_args();
-    eprintln!("```\n{source}```\n");
-
-    let mut lexer = Lexer::new(source);
-    let tokens = lexer.all_tokens();
+fn main() {
+    match parse_args() {
+        Task::Run(options) if options.compact_tape => run(
+            *options,
+            Interpreter::with_tape(io::stdout(), CompactTape::new()),
+        ),
+        Task::Run(options) => run(*options, Interpreter::new(io::stdout())),
+        Task::Build(options) => build(options),
+    }
+}
 
-    tokens.iter().for_each(|tok| eprintln!("{tok}"));
-    eprintln!("\n");
+fn build(options: Build) {
+    let tokens = Lexer::new(options.source).all_tokens();
+    let program = Parser::new(tokens).parse().unwrap_or_else(|err| {
+        eprintln!("{}", Error::from(err));
+        process::exit(1);
+    });
+    // a standalone program has no host functions
+    let code = bytecode::compile(&program, &HashMap::new());
+    let out = match options.target {
+        Target::C => code.to_c(),
+    };
+    let out = out.unwrap_or_else(|err| {
+        eprintln!("{err}");
+        process::exit(1);
+    });
+    match options.output {
+        Some(path) => fs::write(&path, out)
+            .unwrap_or_else(|err| panic!("could not write to {}: {}", path.display(), err)),
+        None => print!("{out}"),
+    }
+}
 
-    let mut parser = Parser::new(tokens);
-    // println!("{parser:?}")
-    let result = parser.parse();
-    match result {
-        Ok(result) => {
-            dbg!(result);
+fn run<T: Tape>(options: Options, mut interpreter: Interpreter<Stdout, T>) {
+    let source = options.source;
+    if options.dump_dot.is_some() {
+        interpreter = interpreter.with_snapshots();
+    }
+    if let Some(path) = options.save {
+        interpreter = interpreter.saving_to(path);
+    }
+    if let Some(snapshot) = &options.resume {
+        interpreter.restore(snapshot);
+    }
+    if let Some(path) = options.input_json {
+        let json = fs::read_to_string(&path)
+            .unwrap_or_else(|err| panic!("could not read from {}: {}", path.display(), err));
+        let groups = import::json::groups_from_json(&json)
+            .unwrap_or_else(|err| panic!("{}: {}", path.display(), err));
+        groups
+            .into_iter()
+            .for_each(|fg| interpreter.tape.add_functional_group(fg));
+    }
+    let ran = match options.tree_walk {
+        true => interpreter.walk(&source),
+        false => interpreter.run(&source),
+    };
+    let failed = match ran {
+        Ok(()) => false,
+        // nothing ran, so there is nothing to write either
+        Err(error @ Error::Parse(_)) => {
+            eprintln!("{error}");
+            process::exit(1);
         }
-        Err(error) => eprintln!("{error}"),
+        Err(error) => {
+            eprintln!("{error}");
+            true
+        }
+    };
+    if let Some(path) = options.dump_dot {
+        // every breakpoint, then the tape at the end, as separate graphs
+        let mut dot: String = interpreter
+            .snapshots
+            .take()
+            .unwrap_or_default()
+            .iter()
+            .map(|snapshot| snapshot.tape.to_dot())
+            .collect();
+        dot.push_str(&interpreter.tape.to_alkane().to_dot());
+        fs::write(&path, dot)
+            .unwrap_or_else(|err| panic!("could not write to {}: {}", path.display(), err));
+    }
+    if let Some(path) = options.dump_mermaid {
+        let mermaid = interpreter
+            .bonded_groups()
+            .iter()
+            .map(|fg| format!("```mermaid\n{}```\n", fg.to_mermaid()))
+            .collect::<Vec<String>>()
+            .join("\n");
+        fs::write(&path, mermaid)
+            .unwrap_or_else(|err| panic!("could not write to {}: {}", path.display(), err));
+    }
+    if let Some(path) = options.dump_smiles {
+        let smiles = interpreter.tape.to_alkane().to_smiles() + "\n";
+        fs::write(&path, smiles)
+            .unwrap_or_else(|err| panic!("could not write to {}: {}", path.display(), err));
+    }
+    if let Some(path) = options.output_json {
+        let groups = interpreter
+            .bonded_groups()
+            .iter()
+            .map(|fg| fg.to_json())
+            .collect();
+        let json = serde_json::to_string_pretty(&serde_json::Value::Array(groups)).unwrap();
+        fs::write(&path, json + "\n")
+            .unwrap_or_else(|err| panic!("could not write to {}: {}", path.display(), err));
+    }
+    // the tape is still written out after an error, but the run failed, like a compiled one does
+    if failed {
+        process::exit(1);
     }
 }
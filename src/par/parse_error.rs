@@ -5,7 +5,8 @@ use crate::lex::tok::{Token, Type};
 #[derive(Debug)]
 pub struct ParseError {
     pub location: (String, u32),
-    pub reason: Reason,
+    // boxed so results carrying a parse error stay small
+    pub reason: Box<Reason>,
     pub last_seen_token: Token,
 }
 
@@ -28,6 +29,10 @@ pub enum Reason {
     OutOfTokens,
     ExpectedDifferentToken { want: Vec<Type>, got: Type },
     NeedAtLeastOneElemental,
+    // a literal the language has, but the parser can't read yet
+    Unsupported(&'static str),
+    // something the lexer couldn't read
+    Invalid(String),
 }
 
 impl Display for Reason {
@@ -40,6 +45,8 @@ impl Display for Reason {
                 f.write_fmt(format_args!("    got:  {}", got))
             }
             Reason::NeedAtLeastOneElemental => f.write_str("need at least one elemental"),
+            Reason::Unsupported(what) => write!(f, "{what} are not supported yet"),
+            Reason::Invalid(message) => f.write_str(message),
         }
     }
 }
@@ -48,7 +55,7 @@ macro_rules! parse_error {
     ($reason:expr, $last_seen_token:expr) => {
         Err(ParseError {
             location: (stdext::function_name!().to_string(), line!()),
-            reason: $reason,
+            reason: Box::new($reason),
             last_seen_token: $last_seen_token,
         })
     };
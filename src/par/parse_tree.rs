@@ -13,6 +13,12 @@ pub enum ParseTree {
         rhs: Vec<ParseTree>,
         newline: Token,
     },
+    // an equation named by an element, which runs where the element is written instead of where it is
+    Definition {
+        name: Token,
+        colon: Token,
+        equation: Box<ParseTree>,
+    },
     Compound {
         coeff: Option<Token>,
         elementals: Box<ParseTree>,
@@ -42,6 +48,20 @@ pub enum ParseTree {
         caret: Token,
         val: Box<ParseTree>,
     },
+    ElementalRationalLiteral {
+        hydrogen: Token,
+        carbon: Token,
+        carbonyl: Token,
+        alkoxy: Box<ParseTree>,
+        numerator: Box<ParseTree>,
+    },
+    SugaredRationalLiteral {
+        hydrogen: Token,
+        caret: Token,
+        numerator: Box<ParseTree>,
+        slash: Token,
+        denominator: Box<ParseTree>,
+    },
     ElementalBooleanLiteral {
         hydrogen: Token,
         boron: Token,
@@ -52,6 +72,11 @@ pub enum ParseTree {
         hydrogen: Token,
         val: Token,
     },
+    SmilesLiteral {
+        hydrogen: Token,
+        dollar: Token,
+        val: Token,
+    },
     ElementalStringLiteral {
         hydrogen: Token,
         sulfur: Token,
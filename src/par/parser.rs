@@ -1,4 +1,4 @@
-use itertools::{PeekingNext, PutBack};
+use itertools::{PeekingNext, PutBackN};
 
 use crate::lex::tok::{Token, Type};
 
@@ -14,7 +14,7 @@ pub enum EquationSide {
 }
 
 pub struct Parser {
-    pub tokens: PutBack<Box<dyn Iterator<Item = Token>>>,
+    pub tokens: PutBackN<Box<dyn Iterator<Item = Token>>>,
     pub used_tokens: Vec<Token>,
 }
 
@@ -23,17 +23,18 @@ impl Parser {
         let tokens = tokens.clone().into_iter();
         let toks: Box<dyn Iterator<Item = Token>> = Box::new(tokens);
         Parser {
-            tokens: itertools::put_back(toks),
+            tokens: itertools::put_back_n(toks),
             used_tokens: Vec::new(),
         }
     }
 
     fn next_token(&mut self) -> Result<Token, ParseError> {
-        dbg!(stdext::function_name!());
         let next = self.tokens.next();
         if let Some(token) = next {
+            if let Type::Invalid(message) = &token.token {
+                return parse_error!(Reason::Invalid(message.clone()), token);
+            }
             self.used_tokens.push(token.clone());
-            // dbg!(&token);
             Ok(token)
         } else {
             parse_error!(
@@ -44,13 +45,12 @@ impl Parser {
     }
 
     fn put_back_token(&mut self) -> Result<bool, ParseError> {
-        dbg!(stdext::function_name!());
-        if self.used_tokens.len() > 0 {
+        if !self.used_tokens.is_empty() {
             let Some(last_token) = self.used_tokens.pop() else {
                 return parse_error!(
                     Reason::OutOfTokens,
                     self.used_tokens.last().unwrap_or(&Token::default()).clone()
-                )
+                );
             };
             self.tokens.put_back(last_token);
             Ok(true)
@@ -60,10 +60,11 @@ impl Parser {
     }
 
     fn peek_token(&mut self) -> Result<Token, ParseError> {
-        dbg!(stdext::function_name!());
         if let Some(peeked) = self.tokens.peeking_next(|_token| true) {
             self.tokens.put_back(peeked.clone());
-            // dbg!(&peeked);
+            if let Type::Invalid(message) = &peeked.token {
+                return parse_error!(Reason::Invalid(message.clone()), peeked);
+            }
             Ok(peeked)
         } else {
             parse_error!(
@@ -74,21 +75,40 @@ impl Parser {
     }
 
     pub fn parse(&mut self) -> Result<ParseTree, ParseError> {
-        dbg!(stdext::function_name!());
         self.program()
     }
 
     pub fn program(&mut self) -> Result<ParseTree, ParseError> {
-        dbg!(stdext::function_name!());
         let mut equations = Vec::new();
         while self.peek_token().is_ok() {
-            equations.push(self.equation()?);
+            let equation = match self.name()? {
+                Some((name, colon)) => ParseTree::Definition {
+                    name,
+                    colon,
+                    equation: Box::new(self.equation()?),
+                },
+                None => self.equation()?,
+            };
+            equations.push(equation);
         }
         Ok(ParseTree::Program { equations })
     }
 
+    // the name of an equation, like `Uue:`, if the next one has one
+    fn name(&mut self) -> Result<Option<(Token, Token)>, ParseError> {
+        if !matches!(self.peek_token()?.token, Type::Element(_)) {
+            return Ok(None);
+        }
+        let name = self.next_token()?;
+        if self.peek_token()?.token != Type::Colon {
+            self.put_back_token()?;
+            return Ok(None);
+        }
+        let colon = self.next_token()?;
+        Ok(Some((name, colon)))
+    }
+
     pub fn equation(&mut self) -> Result<ParseTree, ParseError> {
-        dbg!(stdext::function_name!());
         let side = EquationSide::Left;
         let mut lhs = Vec::new();
         let mut rhs = Vec::new();
@@ -105,12 +125,10 @@ impl Parser {
         let arrow = self.next_token()?;
         match arrow.token {
             Type::Arrow => {
-                let arrow = arrow;
                 let side = EquationSide::Right;
                 rhs.push(self.compound(side.clone())?);
                 loop {
                     let tok = self.peek_token()?;
-                    dbg!(&tok);
                     if let Type::Newline = tok.token {
                         break;
                     } else if let Type::Plus = tok.token {
@@ -122,7 +140,6 @@ impl Parser {
                 }
                 let newline = self.next_token()?;
                 if let Type::Newline = newline.token {
-                    dbg!("finished equation:", &lhs, &arrow, &rhs, &newline);
                     Ok(ParseTree::Equation {
                         lhs,
                         arrow,
@@ -152,7 +169,6 @@ impl Parser {
     }
 
     pub fn compound(&mut self, side: EquationSide) -> Result<ParseTree, ParseError> {
-        dbg!(stdext::function_name!(), &side);
         let coeff = self.coeff()?;
         let elementals = self.elementals(side, false)?;
         let elementals = Box::new(elementals);
@@ -164,7 +180,6 @@ impl Parser {
         side: EquationSide,
         nested: bool,
     ) -> Result<ParseTree, ParseError> {
-        dbg!(stdext::function_name!(), &side);
         let mut elementals = Vec::new();
         let first = self.elemental(side.clone(), nested)?;
         if let Some(first) = first {
@@ -188,7 +203,7 @@ impl Parser {
             let elemental = self.elemental(side.clone(), nested)?;
             if let Some(e) = elemental {
                 elementals.push(e);
-            } else if let None = elemental {
+            } else if elemental.is_none() {
                 break;
             }
         }
@@ -200,7 +215,6 @@ impl Parser {
         side: EquationSide,
         nested: bool,
     ) -> Result<Option<ParseTree>, ParseError> {
-        dbg!(stdext::function_name!(), &side);
         if let Type::LParen = self.peek_token()?.clone().token {
             // nested
             let _lparen = self.next_token()?; // discard lparen
@@ -211,8 +225,11 @@ impl Parser {
 
         // limit the right hand side to only periodics
         if let EquationSide::Right = side {
-            let periodic = self.periodic()?;
-            return Ok(Some(periodic));
+            if let Type::Element(_) = self.peek_token()?.token {
+                let periodic = self.periodic()?;
+                return Ok(Some(periodic));
+            }
+            return Ok(None);
         }
         let token = self.peek_token()?;
         match token.token {
@@ -231,7 +248,6 @@ impl Parser {
     }
 
     fn periodic(&mut self) -> Result<ParseTree, ParseError> {
-        dbg!(stdext::function_name!());
         let element = self.element()?;
         let element = Box::new(element);
         let subscript = self.subscript()?;
@@ -240,7 +256,6 @@ impl Parser {
     }
 
     pub fn element(&mut self) -> Result<ParseTree, ParseError> {
-        dbg!(stdext::function_name!());
         let val = self.next_token()?;
         if let Type::Element(_) = val.token {
             Ok(ParseTree::Element { val })
@@ -256,7 +271,6 @@ impl Parser {
     }
 
     pub fn coeff(&mut self) -> Result<Option<Token>, ParseError> {
-        dbg!(stdext::function_name!());
         let tok = self.peek_token()?;
         if let Type::Number(_) = tok.token {
             let coeff = self.next_token()?;
@@ -267,7 +281,6 @@ impl Parser {
     }
 
     pub fn subscript(&mut self) -> Result<Option<ParseTree>, ParseError> {
-        dbg!(stdext::function_name!());
         let underscore = self.peek_token()?;
         if let Type::Underscore = underscore.token {
             let underscore = self.next_token()?;
@@ -289,7 +302,6 @@ impl Parser {
     }
 
     fn number(&mut self, val: Token) -> Result<ParseTree, ParseError> {
-        dbg!(stdext::function_name!());
         let token = val.clone();
         if let Type::Number(_) = token.token {
             Ok(ParseTree::Number { val })
@@ -305,11 +317,11 @@ impl Parser {
     }
 
     fn literal(&mut self, hydrogen: Token) -> Result<ParseTree, ParseError> {
-        dbg!(stdext::function_name!());
         let token = self.peek_token()?;
         let t = token.clone();
         match token.token {
             Type::Caret => self.sugared_number_literal(hydrogen),
+            Type::Dollar => self.smiles_literal(hydrogen),
             Type::String(_) => self.sugared_string_literal(hydrogen),
             Type::LAngle => self.sugared_pair_literal(hydrogen),
             Type::LBracket => self.sugared_list_literal(hydrogen),
@@ -320,7 +332,7 @@ impl Parser {
                 "O" => self.elemental_number_literal(hydrogen),
                 "S" => self.elemental_string_literal(hydrogen),
                 "N" => self.elemental_pair_literal(hydrogen),
-                "C" => self.elemental_list_map_literal(hydrogen),
+                "C" => self.elemental_carbon_literal(hydrogen),
                 _ => parse_error!(
                     Reason::ExpectedDifferentToken {
                         want: vec![
@@ -338,12 +350,13 @@ impl Parser {
                 ),
             },
             _ => {
-                return parse_error!(
+                parse_error!(
                     Reason::ExpectedDifferentToken {
                         want: vec![
                             Type::Element(String::from("Tr")),
                             Type::Element(String::from("Fa")),
                             Type::Caret,
+                            Type::Dollar,
                             Type::String(String::new()),
                             Type::LAngle,
                             Type::LBracket,
@@ -358,13 +371,15 @@ impl Parser {
     }
 
     pub fn sugared_number_literal(&mut self, hydrogen: Token) -> Result<ParseTree, ParseError> {
-        dbg!(stdext::function_name!());
         // hydrogen caret number
         let caret = self.next_token()?;
         let val = self.next_token()?;
         if let Type::Number(_) = val.token {
             let num = self.number(val)?;
             let num = Box::new(num);
+            if let Type::Slash = self.peek_token()?.token {
+                return self.sugared_rational_literal(hydrogen, caret, num);
+            }
             Ok(ParseTree::SugaredNumberLiteral {
                 hydrogen,
                 caret,
@@ -381,8 +396,83 @@ impl Parser {
         }
     }
 
+    fn sugared_rational_literal(
+        &mut self,
+        hydrogen: Token,
+        caret: Token,
+        numerator: Box<ParseTree>,
+    ) -> Result<ParseTree, ParseError> {
+        // hydrogen caret number slash number
+        let slash = self.next_token()?;
+        let val = self.next_token()?;
+        if let Type::Number(_) = val.token {
+            let denominator = Box::new(self.number(val)?);
+            Ok(ParseTree::SugaredRationalLiteral {
+                hydrogen,
+                caret,
+                numerator,
+                slash,
+                denominator,
+            })
+        } else {
+            parse_error!(
+                Reason::ExpectedDifferentToken {
+                    want: vec![Type::Number("".to_string())],
+                    got: val.clone().token
+                },
+                val
+            )
+        }
+    }
+
+    fn elemental_carbon_literal(&mut self, hydrogen: Token) -> Result<ParseTree, ParseError> {
+        // HCO... is an ester, any other HC... is a list or map
+        let carbon = self.next_token()?;
+        let peeked = self.peek_token()?;
+        if let Type::Element(symbol) = peeked.token {
+            if symbol.as_str() == "O" {
+                return self.elemental_rational_literal(hydrogen, carbon);
+            }
+        }
+        self.put_back_token()?;
+        self.elemental_list_map_literal(hydrogen)
+    }
+
+    fn elemental_rational_literal(
+        &mut self,
+        hydrogen: Token,
+        carbon: Token,
+    ) -> Result<ParseTree, ParseError> {
+        // hydrogen carbon oxygen (oxygen elements) elements
+        let carbonyl = self.next_token()?;
+        let lparen = self.peek_token()?;
+        if lparen.token != Type::LParen {
+            return parse_error!(
+                Reason::ExpectedDifferentToken {
+                    want: vec![Type::LParen],
+                    got: lparen.clone().token
+                },
+                lparen
+            );
+        }
+        let Some(alkoxy) = self.elemental(EquationSide::Left, true)? else {
+            return parse_error!(
+                Reason::NeedAtLeastOneElemental,
+                self.used_tokens.last().unwrap_or(&Token::default()).clone()
+            );
+        };
+        let alkoxy = Box::new(alkoxy);
+        let numerator = Box::new(self.compound(EquationSide::Left)?);
+        Ok(ParseTree::ElementalRationalLiteral {
+            hydrogen,
+            carbon,
+            carbonyl,
+            alkoxy,
+            numerator,
+        })
+    }
+
     fn elemental_number_literal(&mut self, hydrogen: Token) -> Result<ParseTree, ParseError> {
-        dbg!(stdext::function_name!());
         // hydrogen oxygen elements
         let oxygen = self.next_token()?;
         let vals = Box::new(self.compound(EquationSide::Left)?);
@@ -394,12 +484,11 @@ impl Parser {
     }
 
     fn sugared_boolean_literal(&mut self, hydrogen: Token) -> Result<ParseTree, ParseError> {
-        dbg!(stdext::function_name!());
         // HTr or HFa
         let token = self.next_token()?;
         let val = token.clone();
         if let Type::Element(element) = token.token {
-            if element == String::from("Tr") || element == String::from("Fa") {
+            if element == "Tr" || element == "Fa" {
                 Ok(ParseTree::SugaredBooleanLiteral { hydrogen, val })
             } else {
                 parse_error!(
@@ -428,8 +517,15 @@ impl Parser {
     }
 
     fn elemental_boolean_literal(&mut self, hydrogen: Token) -> Result<ParseTree, ParseError> {
+        // hydrogen boron (oxygen hydrogen) elements
         let boron = self.next_token()?;
-        let hydroxide = Box::new(self.elementals(EquationSide::Left, true)?);
+        let Some(hydroxide) = self.elemental(EquationSide::Left, true)? else {
+            return parse_error!(
+                Reason::NeedAtLeastOneElemental,
+                self.used_tokens.last().unwrap_or(&Token::default()).clone()
+            );
+        };
+        let hydroxide = Box::new(hydroxide);
         let val = Box::new(self.elementals(EquationSide::Left, true)?);
         Ok(ParseTree::ElementalBooleanLiteral {
             hydrogen,
@@ -439,38 +535,189 @@ impl Parser {
         })
     }
 
+    fn smiles_literal(&mut self, hydrogen: Token) -> Result<ParseTree, ParseError> {
+        // hydrogen dollar string
+        let dollar = self.next_token()?;
+        let val = self.next_token()?;
+        if let Type::String(_) = val.token {
+            Ok(ParseTree::SmilesLiteral {
+                hydrogen,
+                dollar,
+                val,
+            })
+        } else {
+            parse_error!(
+                Reason::ExpectedDifferentToken {
+                    want: vec![Type::String(String::new())],
+                    got: val.clone().token
+                },
+                val
+            )
+        }
+    }
+
     fn sugared_string_literal(&mut self, hydrogen: Token) -> Result<ParseTree, ParseError> {
-        dbg!(stdext::function_name!());
-        todo!()
+        // hydrogen string
+        let val = self.next_token()?;
+        Ok(ParseTree::SugaredStringLiteral { hydrogen, val })
     }
 
-    fn elemental_string_literal(&mut self, hydrogen: Token) -> Result<ParseTree, ParseError> {
-        dbg!(stdext::function_name!());
-        todo!()
+    fn elemental_string_literal(&mut self, _hydrogen: Token) -> Result<ParseTree, ParseError> {
+        parse_error!(
+            Reason::Unsupported("elemental string literals"),
+            self.peek_token()?
+        )
     }
 
-    fn sugared_pair_literal(&mut self, hydrogen: Token) -> Result<ParseTree, ParseError> {
-        dbg!(stdext::function_name!());
-        todo!()
+    fn sugared_pair_literal(&mut self, _hydrogen: Token) -> Result<ParseTree, ParseError> {
+        parse_error!(
+            Reason::Unsupported("sugared pair literals"),
+            self.peek_token()?
+        )
     }
 
-    fn elemental_pair_literal(&mut self, hydrogen: Token) -> Result<ParseTree, ParseError> {
-        dbg!(stdext::function_name!());
-        todo!()
+    fn elemental_pair_literal(&mut self, _hydrogen: Token) -> Result<ParseTree, ParseError> {
+        parse_error!(
+            Reason::Unsupported("elemental pair literals"),
+            self.peek_token()?
+        )
     }
 
-    fn elemental_list_map_literal(&mut self, hydrogen: Token) -> Result<ParseTree, ParseError> {
-        dbg!(stdext::function_name!());
-        todo!()
+    fn elemental_list_map_literal(&mut self, _hydrogen: Token) -> Result<ParseTree, ParseError> {
+        parse_error!(
+            Reason::Unsupported("elemental list and map literals"),
+            self.peek_token()?
+        )
     }
 
-    fn sugared_list_literal(&mut self, hydrogen: Token) -> Result<ParseTree, ParseError> {
-        dbg!(stdext::function_name!());
-        todo!()
+    fn sugared_list_literal(&mut self, _hydrogen: Token) -> Result<ParseTree, ParseError> {
+        parse_error!(
+            Reason::Unsupported("sugared list literals"),
+            self.peek_token()?
+        )
     }
 
-    fn sugared_map_literal(&mut self, hydrogen: Token) -> Result<ParseTree, ParseError> {
-        dbg!(stdext::function_name!());
-        todo!()
+    fn sugared_map_literal(&mut self, _hydrogen: Token) -> Result<ParseTree, ParseError> {
+        parse_error!(
+            Reason::Unsupported("sugared map literals"),
+            self.peek_token()?
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lex::lexer::Lexer;
+
+    fn parse(source: &str) -> Result<ParseTree, ParseError> {
+        Parser::new(Lexer::new(source.to_string()).all_tokens()).parse()
+    }
+
+    #[test]
+    fn parses_literals_and_instructions() {
+        let Ok(ParseTree::Program { equations }) = parse("H^3Xn + HOLiBe + H$\"O[Li]\" -> H\n")
+        else {
+            panic!("expected a program");
+        };
+        let [ParseTree::Equation { lhs, .. }] = &equations[..] else {
+            panic!("expected one equation, got {equations:?}");
+        };
+        assert_eq!(lhs.len(), 3);
+    }
+
+    #[test]
+    fn parses_named_equations() {
+        let Ok(ParseTree::Program { equations }) = parse("Uue: H^1Xn -> H\nUue + Kr -> H\n") else {
+            panic!("expected a program");
+        };
+        let [ParseTree::Definition { name, equation, .. }, ParseTree::Equation { lhs, .. }] =
+            &equations[..]
+        else {
+            panic!("expected a definition and an equation, got {equations:?}");
+        };
+        assert_eq!(name.token, Type::Element(String::from("Uue")));
+        assert!(matches!(equation.as_ref(), ParseTree::Equation { .. }));
+        assert_eq!(lhs.len(), 2);
+        // only a whole equation can be named
+        assert!(parse("Uue: Uuo: H^1 -> H\n").is_err());
+        assert!(parse("Uue:\n").is_err());
+    }
+
+    #[test]
+    fn parses_right_hand_sides() {
+        let Ok(ParseTree::Program { equations }) = parse("H^1Xn -> H(OH) + XnSH + (OH)H\n") else {
+            panic!("expected a program");
+        };
+        let [ParseTree::Equation { rhs, .. }] = &equations[..] else {
+            panic!("expected one equation, got {equations:?}");
+        };
+        assert_eq!(rhs.len(), 3);
+    }
+
+    #[test]
+    fn parses_strings_and_elemental_booleans() {
+        let Ok(ParseTree::Program { equations }) = parse("H\"a\\\"b\" + HB(OH)O -> H\n") else {
+            panic!("expected a program");
+        };
+        let [ParseTree::Equation { lhs, .. }] = &equations[..] else {
+            panic!("expected one equation, got {equations:?}");
+        };
+        let [ParseTree::Compound {
+            elementals: string, ..
+        }, ParseTree::Compound {
+            elementals: boolean,
+            ..
+        }] = &lhs[..]
+        else {
+            panic!("expected two compounds, got {lhs:?}");
+        };
+        assert!(matches!(
+            string.as_ref(),
+            ParseTree::Elementals { elementals } if matches!(elementals[..], [ParseTree::SugaredStringLiteral { .. }])
+        ));
+        assert!(matches!(
+            boolean.as_ref(),
+            ParseTree::Elementals { elementals } if matches!(elementals[..], [ParseTree::ElementalBooleanLiteral { .. }])
+        ));
+    }
+
+    #[test]
+    fn unsupported_literals_are_errors() {
+        let literals = [
+            ("HSOLi -> H\n", "elemental string literals"),
+            ("H<1,2> -> H\n", "sugared pair literals"),
+            ("HNOLiOBe -> H\n", "elemental pair literals"),
+            ("HCLi -> H\n", "elemental list and map literals"),
+            ("H[1,2] -> H\n", "sugared list literals"),
+            ("H{1:2} -> H\n", "sugared map literals"),
+        ];
+        for (source, literal) in literals {
+            match parse(source) {
+                Err(err) => assert!(
+                    matches!(*err.reason, Reason::Unsupported(what) if what == literal),
+                    "{source}: {err}"
+                ),
+                Ok(tree) => panic!("{source}: expected an error, got {tree:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn missing_newlines_are_errors() {
+        assert!(matches!(
+            parse("H^3Xn -> H"),
+            Err(ParseError { reason, .. }) if matches!(*reason, Reason::OutOfTokens)
+        ));
+    }
+
+    #[test]
+    fn stray_minuses_are_errors() {
+        for source in ["HO--Fe -> H\n", "H^1 - H\n"] {
+            assert!(
+                matches!(parse(source), Err(ParseError { reason, .. }) if matches!(*reason, Reason::Invalid(_))),
+                "{source}"
+            );
+        }
     }
 }
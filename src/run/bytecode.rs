@@ -0,0 +1,455 @@
+// the compact form of a program that the interpreter runs, instead of walking its parse tree.
+// elements are resolved to the instruction they name once, with the direction written after them,
+// literals are built once, and coefficients and subscripts become loops over the ops they repeat.
+// named equations are compiled once each, after the program, and calling one is a `Call` op into its ops.
+// anything the tree-walker fails on becomes an op that fails the same way once it is reached,
+// so both take the same steps and stop at the same place, and `Interpreter::walk` is kept to check against.
+
+use std::{collections::HashMap, fmt::Display, ops::Range};
+
+use crate::{
+    eval::functional_groups::FunctionalGroup,
+    lex::tok::{Token, Type},
+    par::parse_tree::ParseTree,
+};
+
+use super::{
+    interpreter::{count, definitions, directed, literal_group, periodic, unsupported},
+    runtime_error::{Reason, RuntimeError},
+};
+
+// which way along the backbone an instruction goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    // towards the first carbon, written `Li`
+    Up,
+    // towards the last carbon, written `Be`
+    Down,
+}
+
+impl Direction {
+    pub fn from_symbol(symbol: &str) -> Option<Direction> {
+        match symbol {
+            "Li" => Some(Direction::Up),
+            "Be" => Some(Direction::Down),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Direction::Up => f.write_str("Li"),
+            Direction::Down => f.write_str("Be"),
+        }
+    }
+}
+
+// what an element does when it is run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    // moves to the next carbon
+    Na(Direction),
+    // adds a carbon next to the current one
+    K(Direction),
+    Kr,
+    Xn,
+    Fm,
+    Bk,
+    Db,
+    // a named equation, by its index in the program's definitions
+    Call(usize),
+    // a host function, by its index in the interpreter
+    Host(usize),
+    Unknown,
+}
+
+impl Instruction {
+    // the program's definitions are checked first, then host functions, then the built-in instructions.
+    // only the built-ins that move along the backbone take a direction, and go towards the last carbon without one.
+    pub fn resolve(
+        token: &Token,
+        direction: Option<Direction>,
+        definitions: &HashMap<String, usize>,
+        hosts: &HashMap<String, usize>,
+    ) -> Result<Instruction, RuntimeError> {
+        let Type::Element(symbol) = &token.token else {
+            return Ok(Instruction::Unknown);
+        };
+        let instruction = if let Some(definition) = definitions.get(symbol) {
+            Instruction::Call(*definition)
+        } else if let Some(host) = hosts.get(symbol) {
+            Instruction::Host(*host)
+        } else {
+            let towards = direction.unwrap_or(Direction::Down);
+            match symbol.as_str() {
+                "Na" => Instruction::Na(towards),
+                "K" => Instruction::K(towards),
+                "Kr" => Instruction::Kr,
+                "Xn" => Instruction::Xn,
+                "Fm" => Instruction::Fm,
+                "Bk" => Instruction::Bk,
+                "Db" => Instruction::Db,
+                _ => Instruction::Unknown,
+            }
+        };
+        match (instruction, direction) {
+            // an unknown instruction fails when it is run, whatever it was written with
+            (Instruction::Na(_) | Instruction::K(_) | Instruction::Unknown, _) | (_, None) => {
+                Ok(instruction)
+            }
+            (_, Some(direction)) => Err(RuntimeError::new(
+                Reason::Direction {
+                    symbol: symbol.to_string(),
+                    direction,
+                },
+                token.clone(),
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    // an instruction, and the token it was written as, which is only kept for the span of an error
+    Run(Instruction, usize),
+    // bonds a literal's group to the current carbon
+    Bond(usize),
+    // runs the next `len` ops `times` times
+    Repeat { times: usize, len: usize },
+    // runs a definition's ops, and the token that called it
+    Call(usize, usize),
+    Fail(usize),
+}
+
+// a named equation, as the ops it compiled to
+#[derive(Debug, Clone)]
+pub struct Definition {
+    pub symbol: String,
+    pub ops: Range<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Bytecode {
+    // the program's ops, then each definition's
+    pub ops: Vec<Op>,
+    // where the program's ops end
+    pub main: usize,
+    pub definitions: Vec<Definition>,
+    pub tokens: Vec<Token>,
+    // the group each literal bonds, or why it can't be built
+    pub literals: Vec<Result<FunctionalGroup, RuntimeError>>,
+    pub errors: Vec<RuntimeError>,
+}
+
+// host functions are resolved by the index they were registered at,
+// so bytecode only runs on the interpreter it was compiled for
+pub fn compile(program: &ParseTree, hosts: &HashMap<String, usize>) -> Bytecode {
+    let mut compiler = Compiler {
+        hosts,
+        definitions: HashMap::new(),
+        code: Bytecode {
+            ops: Vec::new(),
+            main: 0,
+            definitions: Vec::new(),
+            tokens: Vec::new(),
+            literals: Vec::new(),
+            errors: Vec::new(),
+        },
+    };
+    compiler.program(program);
+    compiler.code
+}
+
+struct Compiler<'a> {
+    hosts: &'a HashMap<String, usize>,
+    // the index of each definition, by the symbol it is named
+    definitions: HashMap<String, usize>,
+    code: Bytecode,
+}
+
+impl<'a> Compiler<'a> {
+    fn program(&mut self, program: &ParseTree) {
+        let ParseTree::Program { equations } = program else {
+            self.fail(unsupported(program, Token::default()));
+            self.code.main = self.code.ops.len();
+            return;
+        };
+        let definitions = match definitions(equations) {
+            Ok(definitions) => definitions,
+            Err(err) => {
+                self.fail(err);
+                self.code.main = self.code.ops.len();
+                return;
+            }
+        };
+        for (idx, (symbol, _)) in definitions.iter().enumerate() {
+            self.definitions.insert(symbol.clone(), idx);
+        }
+        self.equations(
+            equations
+                .iter()
+                .filter(|equation| !matches!(equation, ParseTree::Definition { .. })),
+        );
+        self.code.main = self.code.ops.len();
+        for (symbol, equation) in definitions {
+            let start = self.code.ops.len();
+            self.equations([equation]);
+            let ops = start..self.code.ops.len();
+            self.code.definitions.push(Definition { symbol, ops });
+        }
+    }
+
+    fn equations<'t>(&mut self, equations: impl IntoIterator<Item = &'t ParseTree>) {
+        for equation in equations {
+            let ParseTree::Equation { lhs, .. } = equation else {
+                return self.fail(unsupported(equation, Token::default()));
+            };
+            for compound in lhs {
+                self.compound(compound);
+            }
+        }
+    }
+
+    fn compound(&mut self, compound: &ParseTree) {
+        let ParseTree::Compound { coeff, elementals } = compound else {
+            return self.fail(unsupported(compound, Token::default()));
+        };
+        match count(coeff.as_ref()) {
+            Ok(times) => self.repeat(times, |compiler| compiler.elementals(elementals)),
+            Err(err) => self.fail(err),
+        }
+    }
+
+    fn elementals(&mut self, elementals: &ParseTree) {
+        let ParseTree::Elementals { elementals } = elementals else {
+            return self.elemental(elementals, None);
+        };
+        for (elemental, direction) in directed(elementals) {
+            self.elemental(elemental, direction);
+        }
+    }
+
+    fn elemental(&mut self, elemental: &ParseTree, direction: Option<&ParseTree>) {
+        match elemental {
+            ParseTree::Elementals { .. } => self.elementals(elemental),
+            ParseTree::Periodic { .. } => {
+                let (val, direction, times) = match periodic(elemental, direction) {
+                    Ok(periodic) => periodic,
+                    Err(err) => return self.fail(err),
+                };
+                let instruction =
+                    Instruction::resolve(val, direction, &self.definitions, self.hosts);
+                let instruction = match instruction {
+                    Ok(instruction) => instruction,
+                    Err(err) => return self.fail(err),
+                };
+                self.code.tokens.push(val.clone());
+                let token = self.code.tokens.len() - 1;
+                let op = match instruction {
+                    Instruction::Call(definition) => Op::Call(definition, token),
+                    instruction => Op::Run(instruction, token),
+                };
+                self.repeat(times, |compiler| compiler.code.ops.push(op));
+            }
+            literal => {
+                self.code.literals.push(literal_group(literal));
+                let op = Op::Bond(self.code.literals.len() - 1);
+                self.code.ops.push(op);
+            }
+        }
+    }
+
+    // the ops `body` compiles, run `times` times
+    fn repeat(&mut self, times: usize, body: impl FnOnce(&mut Compiler<'a>)) {
+        if times == 1 {
+            return body(self);
+        }
+        let start = self.code.ops.len();
+        self.code.ops.push(Op::Repeat { times, len: 0 });
+        body(self);
+        let len = self.code.ops.len() - start - 1;
+        self.code.ops[start] = Op::Repeat { times, len };
+    }
+
+    fn fail(&mut self, err: RuntimeError) {
+        self.code.errors.push(err);
+        let op = Op::Fail(self.code.errors.len() - 1);
+        self.code.ops.push(op);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        eval::{traits::Valuable, value::Value},
+        lex::lexer::Lexer,
+        par::parser::Parser,
+        run::interpreter::Interpreter,
+    };
+
+    // everything a run can be told apart by
+    #[derive(Debug, PartialEq)]
+    struct Ran {
+        output: String,
+        // the error it stopped with, as it is printed
+        stopped: Option<String>,
+        values: Vec<Value>,
+        // the step of each snapshot taken at a breakpoint
+        breakpoints: Vec<usize>,
+    }
+
+    fn ran(source: &str, walk: bool) -> Ran {
+        let mut out = Vec::new();
+        let mut interpreter = Interpreter::new(&mut out).with_snapshots();
+        interpreter
+            .register("Ubn_2", |args| match args.as_slice() {
+                [Value::Number(l), Value::Number(r)] => Ok(Value::from(l * r)),
+                _ => Err(String::from("expected numbers")),
+            })
+            .unwrap();
+        interpreter
+            .register("Ubu", |_| Err(String::from("nope")))
+            .unwrap();
+        let ran = match walk {
+            true => interpreter.walk(source),
+            false => interpreter.run(source),
+        };
+        let values = interpreter
+            .bonded_groups()
+            .iter()
+            .map(|fg| fg.value())
+            .collect();
+        let breakpoints = interpreter
+            .snapshots
+            .iter()
+            .flatten()
+            .map(|snapshot| snapshot.step)
+            .collect();
+        drop(interpreter);
+        Ran {
+            output: String::from_utf8(out).unwrap(),
+            stopped: ran.err().map(|err| err.to_string()),
+            values,
+            breakpoints,
+        }
+    }
+
+    // the bytecode has to run exactly like the tree it was compiled from
+    fn same(programs: &[&str]) {
+        for source in programs {
+            let run = ran(source, false);
+            assert_eq!(run, ran(source, true), "{source}");
+        }
+    }
+
+    #[test]
+    fn prints_the_same() {
+        same(&[
+            "H^3Xn -> H\n",
+            "H^1Kr + H^2Xn -> H\n",
+            "H^1H^2Kr_2 + Xn_3 -> H\n",
+            "HOC_2 + HOLiBe + Fm -> H\n",
+            "3H^1 + 2Xn -> H\n",
+            "H^1H^3Db + Xn -> H\n",
+            "H$\"O[Li]\" + Xn + Fm -> H\n",
+            "H^3Xn -> H\nH^4Xn -> H\n",
+        ]);
+    }
+
+    #[test]
+    fn breaks_at_the_same_steps() {
+        same(&[
+            "H^1Bk + 2H^2Bk + Xn -> H\n",
+            "2H^1BkXn -> H\n",
+            "Bk_3 + H^1 -> H\n",
+        ]);
+        assert_eq!(ran("H^1Bk + 2H^2Bk -> H\n", false).breakpoints, [2, 4, 6]);
+    }
+
+    #[test]
+    fn calls_hosts_the_same() {
+        same(&[
+            "H^6H^7UbnXn -> H\n",
+            "H^1H^2H^3Ubn_2Xn -> H\n",
+            "H^1UbnXn -> H\n",
+            "H^1Ubu + Xn -> H\n",
+            "H^1HTrUbn -> H\n",
+        ]);
+    }
+
+    #[test]
+    fn moves_the_same() {
+        same(&[
+            "H^1KBe + NaBe + H^2Xn + NaLi + Xn -> H\n",
+            "H^1KLi + NaLi + H^2Xn + Na + Xn -> H\n",
+            "K_2 + NaBe_2 + H^1Xn + NaLi_2 + Xn -> H\n",
+            "2(KNaBe) + H^3 + 2NaLi + Xn -> H\n",
+            "H^1Xn + NaBe -> H\n",
+            "H^1Xn + KNaLiNaLi -> H\n",
+            "H^1Bk + KNaBeBk + H^2Bk + NaLiBkXn -> H\n",
+        ]);
+        let moved = ran("H^1KBe + NaBe + H^2Xn + NaLi + Xn -> H\n", false);
+        assert_eq!(moved.output, "2\n1\n");
+    }
+
+    #[test]
+    fn calls_the_same() {
+        same(&[
+            "Uue: H^1Xn -> H\nUue_2 + Uuo -> H\nUuo: H^2Uue -> H\n",
+            "Uue: H^1Bk -> H\n2Uue + Xn -> H\n",
+            "Uue: H^6H^7Ubn -> H\nUue + Xn -> H\n",
+            "Ubn: H^5 -> H\nH^6H^7UbnXn -> H\n",
+            "Uue: KNaBe -> H\nUue_3 + H^1 + NaLi_3 + Xn -> H\n",
+            "Uue: Uue -> H\nH^1Xn + Uue -> H\n",
+            "Uue: H^1Xn + Zz -> H\nUue + Xn -> H\n",
+            "Uue: H^1Xn -> H\nH^2Xn -> H\nUue: H^3Xn -> H\n",
+            "Kr: H^1Xn -> H\nH^2Xn -> H\n",
+            "Uue: H^1Xn -> H\nUueBe -> H\n",
+        ]);
+        let called = ran(
+            "Uue: H^1Xn -> H\nUue_2 + Uuo -> H\nUuo: H^2Uue -> H\n",
+            false,
+        );
+        assert_eq!(called.output, "1\n1\n1\n1\n1\n2\n1\n");
+        assert!(ran("Uue: Uue -> H\nUue -> H\n", false).stopped.is_some());
+    }
+
+    #[test]
+    fn compiles_definitions_once() {
+        let mut out = Vec::new();
+        let interpreter = Interpreter::new(&mut out);
+        let tokens = Lexer::new(String::from("Uue: H^1Xn -> H\n3Uue + Uue_2 -> H\n")).all_tokens();
+        let code = interpreter.compile(&Parser::new(tokens).parse().unwrap());
+        let [definition] = &code.definitions[..] else {
+            panic!("expected one definition, got {:?}", code.definitions);
+        };
+        assert_eq!(definition.symbol, "Uue");
+        assert_eq!(definition.ops, code.main..code.ops.len());
+        assert_eq!(
+            code.ops[definition.ops.clone()],
+            [Op::Bond(0), Op::Run(Instruction::Xn, 2)]
+        );
+        assert!(code.ops[..code.main]
+            .iter()
+            .all(|op| !matches!(op, Op::Run(..) | Op::Bond(_))));
+    }
+
+    #[test]
+    fn fails_the_same() {
+        same(&[
+            "H^1Xn + Zz + Xn -> H\n",
+            "H^1Xn + 2Zz -> H\n",
+            "H^6H^0DbXn -> H\n",
+            "H^6Db -> H\n",
+            "H^1Xn + H$\"O[Li][Be]\" + Xn -> H\n",
+            "H^1Xn + HOFe_999999999999999999999999 + Xn -> H\n",
+            "H^1Xn + H^99999999999999999999999 + Xn -> H\n",
+        ]);
+        for source in ["H^1Xn + Zz + Xn -> H\n", "H^6H^0DbXn -> H\n"] {
+            assert!(ran(source, false).stopped.is_some(), "{source}");
+        }
+    }
+}
@@ -0,0 +1,116 @@
+// functions from the program embedding the interpreter, bound to systematic element names.
+// the name is written like an element with a subscript, which is the number of values it takes:
+// `Ubn_2` takes the last 2 groups bonded to the current carbon, and bonds what it returns instead.
+// like any other subscript, a missing one is 1.
+// discovered elements can be bound by their systematic name, like `Uuq` for flerovium,
+// since that is never how their symbol is written, so it can't be an instruction.
+
+use std::{fmt::Display, str::FromStr};
+
+use crate::eval::{
+    element::{Element, ElementError},
+    value::Value,
+};
+
+pub type HostFunction = Box<dyn FnMut(Vec<Value>) -> Result<Value, String>>;
+
+pub struct Host {
+    pub symbol: String,
+    pub arity: usize,
+    pub f: HostFunction,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BindError {
+    Element(ElementError),
+    // the symbols of discovered elements are instructions, so only systematic names can be bound
+    Reserved(String),
+    Subscript(String),
+}
+
+impl Display for BindError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BindError::Element(err) => write!(f, "{err}"),
+            BindError::Reserved(symbol) => write!(
+                f,
+                "`{symbol}` is reserved, only systematic element names can be bound"
+            ),
+            BindError::Subscript(subscript) => write!(
+                f,
+                "expected the number of values to take as a positive subscript, got `_{subscript}`"
+            ),
+        }
+    }
+}
+
+// the symbol and arity of a name like `Ubn_2`
+pub fn parse_name(name: &str) -> Result<(String, usize), BindError> {
+    let (symbol, arity) = match name.split_once('_') {
+        // only digits, like the subscripts of a program, so not `+2`
+        Some((symbol, subscript)) if subscript.bytes().all(|b| b.is_ascii_digit()) => {
+            match subscript.parse() {
+                Ok(arity) if arity > 0 => (symbol, arity),
+                _ => return Err(BindError::Subscript(subscript.to_string())),
+            }
+        }
+        Some((_, subscript)) => return Err(BindError::Subscript(subscript.to_string())),
+        None => (name, 1),
+    };
+    match Element::from_str(symbol) {
+        Ok(Element::Heavy(symbol)) => Ok((symbol, arity)),
+        Err(ElementError::BelowHeavy { symbol, .. }) => Ok((symbol, arity)),
+        Ok(_) => Err(BindError::Reserved(symbol.to_string())),
+        Err(err) => Err(BindError::Element(err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_names() {
+        assert_eq!(parse_name("Ubn_2"), Ok((String::from("Ubn"), 2)));
+        assert_eq!(parse_name("Ubn"), Ok((String::from("Ubn"), 1)));
+        assert_eq!(parse_name("Uuuo_3"), Ok((String::from("Uuuo"), 3)));
+    }
+
+    #[test]
+    fn only_undiscovered_elements_can_be_bound() {
+        assert_eq!(
+            parse_name("Fe_2"),
+            Err(BindError::Reserved(String::from("Fe")))
+        );
+        assert_eq!(
+            parse_name("Fl"),
+            Err(BindError::Reserved(String::from("Fl")))
+        );
+        assert!(matches!(parse_name("Xx"), Err(BindError::Element(_))));
+    }
+
+    #[test]
+    fn discovered_elements_bind_by_systematic_name() {
+        // `Uuq` is flerovium, whose symbol is `Fl`
+        assert_eq!(parse_name("Uuq_2"), Ok((String::from("Uuq"), 2)));
+        assert_eq!(parse_name("Uue"), Ok((String::from("Uue"), 1)));
+    }
+
+    #[test]
+    fn subscripts_are_positive_numbers() {
+        assert_eq!(
+            parse_name("Ubn_0"),
+            Err(BindError::Subscript(String::from("0")))
+        );
+        assert_eq!(
+            parse_name("Ubn_x"),
+            Err(BindError::Subscript(String::from("x")))
+        );
+        for subscript in ["+2", "-2", " 2", ""] {
+            assert_eq!(
+                parse_name(&format!("Ubn_{subscript}")),
+                Err(BindError::Subscript(subscript.to_string()))
+            );
+        }
+    }
+}
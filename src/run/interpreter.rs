@@ -0,0 +1,1043 @@
+use std::{collections::HashMap, fs, io::Write, ops::Range, path::PathBuf, str::FromStr};
+
+use itertools::Itertools;
+
+use crate::{
+    error::Error,
+    eval::{
+        element::Element,
+        functional_groups::{alkane::Alkane, classify::ClassifyError, FunctionalGroup},
+        number::{self, Number},
+        traits::{Valuable, Weighable},
+        value::{Value, ValueError},
+    },
+    import::smiles::SmilesError,
+    lex::{
+        lexer::Lexer,
+        tok::{Token, Type},
+    },
+    par::{parse_tree::ParseTree, parser::Parser},
+};
+
+use super::{
+    bytecode::{self, Bytecode, Direction, Instruction, Op},
+    host::{self, BindError, Host},
+    runtime_error::{Reason, RuntimeError},
+    snapshot::Snapshot,
+    tape::Tape,
+};
+
+// how deep calls into definitions can nest
+pub(crate) const MAX_DEPTH: usize = 256;
+
+// runs programs against a tape, which is an `Alkane` unless another `Tape` is given
+pub struct Interpreter<W: Write, T: Tape = Alkane> {
+    pub tape: T,
+    out: W,
+    // the source of the program being run, kept so snapshots can be resumed on their own
+    source: String,
+    // the number of instructions run and literals bonded so far
+    step: usize,
+    // the steps left to skip, when resuming from a snapshot
+    skip: usize,
+    // the calls into definitions the run is in
+    depth: usize,
+    // whether a snapshot was restored since the last run, which has to be of the snapshot's program
+    restored: bool,
+    // snapshots taken at `Bk` breakpoints, if enabled
+    pub snapshots: Option<Vec<Snapshot>>,
+    // where to save the latest snapshot, if anywhere
+    save: Option<PathBuf>,
+    // host functions, in the order they were first registered
+    hosts: Vec<Host>,
+    // where each host function is in `hosts`, by the symbol it is bound to
+    host_names: HashMap<String, usize>,
+}
+
+impl<W: Write> Interpreter<W> {
+    pub fn new(out: W) -> Interpreter<W> {
+        Interpreter::with_tape(out, Alkane::new())
+    }
+}
+
+impl<W: Write, T: Tape> Interpreter<W, T> {
+    pub fn with_tape(out: W, tape: T) -> Interpreter<W, T> {
+        Interpreter {
+            tape,
+            out,
+            source: String::new(),
+            step: 0,
+            skip: 0,
+            depth: 0,
+            restored: false,
+            snapshots: None,
+            save: None,
+            hosts: Vec::new(),
+            host_names: HashMap::new(),
+        }
+    }
+
+    // keep a snapshot at every `Bk`, instead of ignoring them
+    pub fn with_snapshots(mut self) -> Interpreter<W, T> {
+        self.snapshots = Some(Vec::new());
+        self
+    }
+
+    // save a snapshot to `path` at every `Bk`, replacing the one before it
+    pub fn saving_to(mut self, path: PathBuf) -> Interpreter<W, T> {
+        self.save = Some(path);
+        self
+    }
+
+    // binds a function to an undiscovered element, with the number of values it takes as a subscript,
+    // like `Ubn_2`. errors it returns stop the run, at the element that called it.
+    pub fn register<F>(&mut self, name: &str, f: F) -> Result<(), BindError>
+    where
+        F: FnMut(Vec<Value>) -> Result<Value, String> + 'static,
+    {
+        let (symbol, arity) = host::parse_name(name)?;
+        let host = Host {
+            symbol: symbol.clone(),
+            arity,
+            f: Box::new(f),
+        };
+        // registering a symbol again replaces it in place, so compiled programs call the new one
+        match self.host_names.get(&symbol) {
+            Some(idx) => self.hosts[*idx] = host,
+            None => {
+                self.host_names.insert(symbol, self.hosts.len());
+                self.hosts.push(host);
+            }
+        }
+        Ok(())
+    }
+
+    // the state of the run so far
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            source: self.source.clone(),
+            step: self.step,
+            tape: self.tape.to_alkane(),
+        }
+    }
+
+    // go back (or forward) to a snapshot of the same program.
+    // the next `run` skips the steps before it, and carries on from there,
+    // so it has to be given the snapshot's source.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.source = snapshot.source.clone();
+        self.skip = snapshot.step;
+        self.restored = true;
+        self.tape = T::from_alkane(snapshot.tape.clone());
+    }
+
+    // lexes, parses, compiles and runs a program against the tape as it is
+    pub fn run(&mut self, source: &str) -> Result<(), Error> {
+        let program = self.parse(source)?;
+        let code = self.compile(&program);
+        Ok(self.run_bytecode(&code)?)
+    }
+
+    // the same as `run`, but walking the parse tree instead of compiling it.
+    // it is slower, but simple enough to check the compiler against.
+    pub fn walk(&mut self, source: &str) -> Result<(), Error> {
+        let program = self.parse(source)?;
+        Ok(self.run_program(&program)?)
+    }
+
+    fn parse(&mut self, source: &str) -> Result<ParseTree, Error> {
+        // the steps to skip only line up with the program the snapshot was taken from
+        if self.restored && source != self.source {
+            return Err(RuntimeError::new(Reason::Resume, Token::default()).into());
+        }
+        let tokens = Lexer::new(source.to_string()).all_tokens();
+        let program = Parser::new(tokens).parse()?;
+        self.source = source.to_string();
+        self.restored = false;
+        Ok(program)
+    }
+
+    // the program as bytecode, with the host functions registered so far
+    pub fn compile(&self, program: &ParseTree) -> Bytecode {
+        bytecode::compile(program, &self.host_names)
+    }
+
+    // runs a program compiled by this interpreter.
+    // snapshots keep the source last given to `run` or `restore`.
+    pub fn run_bytecode(&mut self, code: &Bytecode) -> Result<(), RuntimeError> {
+        self.step = 0;
+        self.depth = 0;
+        self.ops(code, 0..code.main)?;
+        self.out
+            .flush()
+            .map_err(|err| RuntimeError::new(Reason::Io(err.to_string()), Token::default()))
+    }
+
+    fn ops(&mut self, code: &Bytecode, ops: Range<usize>) -> Result<(), RuntimeError> {
+        let mut pc = ops.start;
+        while pc < ops.end {
+            match code.ops[pc] {
+                Op::Run(instruction, token) => {
+                    if self.step() {
+                        self.execute(instruction, &code.tokens[token])?;
+                    }
+                }
+                Op::Bond(literal) => {
+                    if self.step() {
+                        let fg = code.literals[literal].clone()?;
+                        self.tape.add_functional_group(fg);
+                    }
+                }
+                Op::Repeat { times, len } => {
+                    for _ in 0..times {
+                        self.ops(code, pc + 1..pc + 1 + len)?;
+                    }
+                    pc += len;
+                }
+                Op::Call(definition, token) => {
+                    let definition = &code.definitions[definition];
+                    self.enter(&definition.symbol, &code.tokens[token])?;
+                    self.ops(code, definition.ops.clone())?;
+                    self.depth -= 1;
+                }
+                Op::Fail(error) => return Err(code.errors[error].clone()),
+            }
+            pc += 1;
+        }
+        Ok(())
+    }
+
+    // walks a program that was already parsed.
+    // snapshots keep the source last given to `run` or `restore`.
+    pub fn run_program(&mut self, program: &ParseTree) -> Result<(), RuntimeError> {
+        self.step = 0;
+        self.depth = 0;
+        let ParseTree::Program { equations } = program else {
+            return Err(unsupported(program, Token::default()));
+        };
+        let named = definitions(equations)?;
+        let definitions = Definitions {
+            names: named
+                .iter()
+                .enumerate()
+                .map(|(idx, (symbol, _))| (symbol.clone(), idx))
+                .collect(),
+            equations: named,
+        };
+        for equation in equations {
+            match equation {
+                // named equations only run when they are called
+                ParseTree::Definition { .. } => {}
+                equation => self.equation(equation, &definitions)?,
+            }
+        }
+        self.out
+            .flush()
+            .map_err(|err| RuntimeError::new(Reason::Io(err.to_string()), Token::default()))
+    }
+
+    fn equation(
+        &mut self,
+        equation: &ParseTree,
+        definitions: &Definitions,
+    ) -> Result<(), RuntimeError> {
+        let ParseTree::Equation { lhs, .. } = equation else {
+            return Err(unsupported(equation, Token::default()));
+        };
+        for compound in lhs {
+            self.compound(compound, definitions)?;
+        }
+        Ok(())
+    }
+
+    fn compound(
+        &mut self,
+        compound: &ParseTree,
+        definitions: &Definitions,
+    ) -> Result<(), RuntimeError> {
+        let ParseTree::Compound { coeff, elementals } = compound else {
+            return Err(unsupported(compound, Token::default()));
+        };
+        for _ in 0..count(coeff.as_ref())? {
+            self.elementals(elementals, definitions)?;
+        }
+        Ok(())
+    }
+
+    fn elementals(
+        &mut self,
+        elementals: &ParseTree,
+        definitions: &Definitions,
+    ) -> Result<(), RuntimeError> {
+        let ParseTree::Elementals { elementals } = elementals else {
+            return self.elemental(elementals, None, definitions);
+        };
+        for (elemental, direction) in directed(elementals) {
+            self.elemental(elemental, direction, definitions)?;
+        }
+        Ok(())
+    }
+
+    fn elemental(
+        &mut self,
+        elemental: &ParseTree,
+        direction: Option<&ParseTree>,
+        definitions: &Definitions,
+    ) -> Result<(), RuntimeError> {
+        match elemental {
+            ParseTree::Elementals { .. } => self.elementals(elemental, definitions),
+            ParseTree::Periodic { .. } => {
+                let (val, direction, times) = periodic(elemental, direction)?;
+                let instruction =
+                    Instruction::resolve(val, direction, &definitions.names, &self.host_names)?;
+                for _ in 0..times {
+                    match instruction {
+                        Instruction::Call(definition) => {
+                            let (symbol, equation) = &definitions.equations[definition];
+                            self.enter(symbol, val)?;
+                            self.equation(equation, definitions)?;
+                            self.depth -= 1;
+                        }
+                        instruction => {
+                            if self.step() {
+                                self.execute(instruction, val)?;
+                            }
+                        }
+                    }
+                }
+                Ok(())
+            }
+            literal => {
+                if self.step() {
+                    let fg = literal_group(literal)?;
+                    self.tape.add_functional_group(fg);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    // counts a step, and whether to run it, which it isn't while skipping to a snapshot
+    fn step(&mut self) -> bool {
+        self.step += 1;
+        if self.skip > 0 {
+            self.skip -= 1;
+            return false;
+        }
+        true
+    }
+
+    // goes into a call to a definition, unless it nests calls too deep
+    fn enter(&mut self, symbol: &str, token: &Token) -> Result<(), RuntimeError> {
+        if self.depth == MAX_DEPTH {
+            let reason = Reason::Depth {
+                symbol: symbol.to_string(),
+                depth: MAX_DEPTH,
+            };
+            return Err(RuntimeError::new(reason, token.clone()));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn execute(&mut self, instruction: Instruction, token: &Token) -> Result<(), RuntimeError> {
+        let output = match instruction {
+            Instruction::Na(direction) => return self.move_to(direction, token),
+            Instruction::K(direction) => {
+                match direction {
+                    Direction::Up => self.tape.add_carbon_before(),
+                    Direction::Down => self.tape.add_carbon_after(),
+                }
+                String::new()
+            }
+            Instruction::Kr => self.bonded_values().join("\n"),
+            Instruction::Xn => self.bonded_values().join("\n") + "\n",
+            Instruction::Fm => self
+                .bonded_groups()
+                .iter()
+                .map(|fg| formula_line(fg))
+                .collect(),
+            Instruction::Bk => {
+                self.breakpoint(token)?;
+                String::new()
+            }
+            Instruction::Db => return self.divide(token),
+            // calls are run by whatever is running the program, which knows where the definition is
+            Instruction::Call(_) => unreachable!(),
+            Instruction::Host(host) => return self.call(host, token),
+            Instruction::Unknown => return Err(unknown_instruction(token)),
+        };
+        self.out
+            .write_all(output.as_bytes())
+            .map_err(|err| RuntimeError::new(Reason::Io(err.to_string()), token.clone()))
+    }
+
+    // moves to the next carbon towards `direction`, which has to be there
+    fn move_to(&mut self, direction: Direction, token: &Token) -> Result<(), RuntimeError> {
+        let moved = match direction {
+            Direction::Up => self.tape.move_up(),
+            Direction::Down => self.tape.move_down(),
+        };
+        match moved {
+            true => Ok(()),
+            false => Err(RuntimeError::new(Reason::OffTape(direction), token.clone())),
+        }
+    }
+
+    // unbonds the last `arity` groups bonded to the current carbon, for an instruction that takes them
+    fn take(
+        &mut self,
+        symbol: &str,
+        arity: usize,
+        token: &Token,
+    ) -> Result<Vec<FunctionalGroup>, RuntimeError> {
+        self.tape.take_bonded_groups(arity).ok_or_else(|| {
+            RuntimeError::new(
+                Reason::Arity {
+                    symbol: symbol.to_string(),
+                    arity,
+                    bonded: self.bonded_groups().len(),
+                },
+                token.clone(),
+            )
+        })
+    }
+
+    // divides the second to last group bonded to the current carbon by the last, bonding the quotient in their place
+    fn divide(&mut self, token: &Token) -> Result<(), RuntimeError> {
+        let groups = self.take("Db", 2, token)?;
+        let [l, r] = [&groups[0], &groups[1]].map(|fg| fg.value());
+        let value = l
+            .checked_div(r)
+            .map_err(|err| RuntimeError::new(Reason::Value(err), token.clone()))?;
+        self.tape.add_functional_group(FunctionalGroup::from(value));
+        Ok(())
+    }
+
+    // calls a host function with the values it takes, bonding what it returns in their place
+    fn call(&mut self, host: usize, token: &Token) -> Result<(), RuntimeError> {
+        let (symbol, arity) = (self.hosts[host].symbol.clone(), self.hosts[host].arity);
+        let groups = self.take(&symbol, arity, token)?;
+        let args = groups.iter().map(|fg| fg.value()).collect();
+        let value = (self.hosts[host].f)(args)
+            .map_err(|err| RuntimeError::new(Reason::Host { symbol, err }, token.clone()))?;
+        self.tape.add_functional_group(FunctionalGroup::from(value));
+        Ok(())
+    }
+
+    fn breakpoint(&mut self, token: &Token) -> Result<(), RuntimeError> {
+        if self.snapshots.is_none() && self.save.is_none() {
+            return Ok(());
+        }
+        let snapshot = self.snapshot();
+        if let Some(path) = &self.save {
+            // everything written before the snapshot has to be out by the time it is saved
+            self.out
+                .flush()
+                .map_err(|err| RuntimeError::new(Reason::Io(err.to_string()), token.clone()))?;
+            fs::write(path, snapshot.to_string()).map_err(|err| {
+                RuntimeError::new(
+                    Reason::Save(format!("{}: {}", path.display(), err)),
+                    token.clone(),
+                )
+            })?;
+        }
+        if let Some(snapshots) = &mut self.snapshots {
+            snapshots.push(snapshot);
+        }
+        Ok(())
+    }
+
+    // the functional groups bonded to the current carbon
+    pub fn bonded_groups(&self) -> Vec<&FunctionalGroup> {
+        self.tape.bonded_groups()
+    }
+
+    // the values of the groups bonded to the current carbon, as they are printed
+    fn bonded_values(&self) -> Vec<String> {
+        self.bonded_groups().iter().map(|fg| printed(fg)).collect()
+    }
+}
+
+// a group's value as `Kr` and `Xn` print it, which is without the quotes for strings
+pub(crate) fn printed(fg: &FunctionalGroup) -> String {
+    match fg.value() {
+        Value::String(s) => s.into_iter().collect(),
+        v => v.to_string(),
+    }
+}
+
+// a group's line of `Fm` output
+pub(crate) fn formula_line(fg: &FunctionalGroup) -> String {
+    let formula = fg.formula();
+    match formula.molar_mass() {
+        Some(mass) => format!("{formula} {mass:.3} g/mol\n"),
+        None => {
+            let undiscovered = formula.undiscovered().iter().join(", ");
+            format!("{formula} (no molar mass for undiscovered {undiscovered})\n")
+        }
+    }
+}
+
+// a program's definitions, while walking it
+struct Definitions<'a> {
+    // each definition's symbol and equation
+    equations: Vec<(String, &'a ParseTree)>,
+    // where each definition is, by its symbol
+    names: HashMap<String, usize>,
+}
+
+// the equations a program names, with the symbol each is named, in the order they are written.
+// they are all found before the program runs, so one can be called before it is written.
+pub(crate) fn definitions(
+    equations: &[ParseTree],
+) -> Result<Vec<(String, &ParseTree)>, RuntimeError> {
+    let mut definitions: Vec<(String, &ParseTree)> = Vec::new();
+    for equation in equations {
+        let ParseTree::Definition { name, equation, .. } = equation else {
+            continue;
+        };
+        let Type::Element(symbol) = &name.token else {
+            return Err(unsupported(equation, name.clone()));
+        };
+        // the same names host functions can be bound to, which are never built-in instructions
+        let (symbol, _) = host::parse_name(symbol)
+            .map_err(|err| RuntimeError::new(Reason::Definition(err), name.clone()))?;
+        if definitions.iter().any(|(defined, _)| *defined == symbol) {
+            return Err(RuntimeError::new(Reason::Redefined(symbol), name.clone()));
+        }
+        definitions.push((symbol, equation));
+    }
+    Ok(definitions)
+}
+
+// each elemental, with the direction written after it, if any.
+// `Li` or `Be` right after an element without a subscript is its direction, and takes the subscript, like `NaBe_2`.
+// anywhere else, they are elements of their own.
+pub(crate) fn directed(elementals: &[ParseTree]) -> Vec<(&ParseTree, Option<&ParseTree>)> {
+    let mut elementals = elementals.iter().peekable();
+    let mut directed = Vec::new();
+    while let Some(elemental) = elementals.next() {
+        let direction = match elemental {
+            ParseTree::Periodic { subscript, .. } if subscript.is_none() => {
+                elementals.next_if(|next| as_direction(next).is_some())
+            }
+            _ => None,
+        };
+        directed.push((elemental, direction));
+    }
+    directed
+}
+
+fn as_direction(periodic: &ParseTree) -> Option<Direction> {
+    let ParseTree::Periodic { element, .. } = periodic else {
+        return None;
+    };
+    match element.as_ref() {
+        ParseTree::Element { val } => match &val.token {
+            Type::Element(symbol) => Direction::from_symbol(symbol),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// the element a periodic runs, the direction `directed` found after it, and how many times it runs
+pub(crate) fn periodic<'a>(
+    periodic: &'a ParseTree,
+    direction: Option<&ParseTree>,
+) -> Result<(&'a Token, Option<Direction>, usize), RuntimeError> {
+    let ParseTree::Periodic { element, subscript } = periodic else {
+        return Err(unsupported(periodic, Token::default()));
+    };
+    let ParseTree::Element { val } = element.as_ref() else {
+        return Err(unsupported(element, Token::default()));
+    };
+    let subscript = match direction {
+        Some(ParseTree::Periodic { subscript, .. }) => subscript,
+        _ => subscript,
+    };
+    let times = match subscript.as_ref() {
+        Some(ParseTree::Subscript { val, .. }) => count(Some(val))?,
+        _ => 1,
+    };
+    Ok((val, direction.and_then(as_direction), times))
+}
+
+pub(crate) fn unknown_instruction(token: &Token) -> RuntimeError {
+    let symbol = match &token.token {
+        Type::Element(symbol) => symbol.to_string(),
+        token => token.to_string(),
+    };
+    RuntimeError::new(Reason::UnknownInstruction(symbol), token.clone())
+}
+
+pub(crate) fn unsupported(tree: &ParseTree, token: Token) -> RuntimeError {
+    RuntimeError::new(Reason::Unsupported(format!("{tree:?}")), token)
+}
+
+// a coefficient or subscript, which is 1 if missing
+pub(crate) fn count(token: Option<&Token>) -> Result<usize, RuntimeError> {
+    let Some(token) = token else {
+        return Ok(1);
+    };
+    match &token.token {
+        Type::Number(digits) => digits.parse().map_err(|_| {
+            RuntimeError::new(
+                Reason::Value(ValueError::Overflow(digits.to_string())),
+                token.clone(),
+            )
+        }),
+        _ => Err(RuntimeError::new(
+            Reason::Unsupported(token.token.to_string()),
+            token.clone(),
+        )),
+    }
+}
+
+// the group a literal bonds to the tape.
+// SMILES literals keep the shape they were written in, everything else is built from its value.
+pub(crate) fn literal_group(literal: &ParseTree) -> Result<FunctionalGroup, RuntimeError> {
+    match literal {
+        ParseTree::SmilesLiteral { val, .. } => {
+            let Type::String(smiles) = &val.token else {
+                return Err(unsupported(literal, val.clone()));
+            };
+            FunctionalGroup::from_smiles(smiles).map_err(|err| {
+                let reason = match err {
+                    // a well-formed molecule whose value is too big
+                    SmilesError::Unrecognised(ClassifyError::Value(err)) => Reason::Value(err),
+                    err => Reason::Smiles(err),
+                };
+                RuntimeError::new(reason, val.clone())
+            })
+        }
+        literal => Ok(FunctionalGroup::from(literal_value(literal)?)),
+    }
+}
+
+// the value a literal bonds to the tape
+fn literal_value(literal: &ParseTree) -> Result<Value, RuntimeError> {
+    match literal {
+        ParseTree::SugaredNumberLiteral { hydrogen, val, .. } => {
+            Ok(Value::from(number_token(val, hydrogen)?))
+        }
+        ParseTree::SugaredRationalLiteral {
+            hydrogen,
+            numerator,
+            denominator,
+            ..
+        } => {
+            let n = number_token(numerator, hydrogen)?;
+            let d = number_token(denominator, hydrogen)?;
+            divide(n, d, hydrogen)
+        }
+        ParseTree::SugaredBooleanLiteral { val, .. } => Ok(Value::Boolean(
+            val.token == Type::Element(String::from("Tr")),
+        )),
+        ParseTree::SugaredStringLiteral { val, .. } => {
+            let Type::String(chars) = &val.token else {
+                return Err(unsupported(literal, val.clone()));
+            };
+            // the lexer keeps the backslash of an escaped quote
+            Ok(Value::String(chars.replace("\\\"", "\"").chars().collect()))
+        }
+        ParseTree::ElementalNumberLiteral { hydrogen, vals, .. } => {
+            Ok(Value::from(weigh(vals, hydrogen)?))
+        }
+        ParseTree::ElementalRationalLiteral {
+            hydrogen,
+            alkoxy,
+            numerator,
+            ..
+        } => {
+            // the alkoxy group starts with its oxygen, which isn't part of the denominator
+            let d = match alkoxy.as_ref() {
+                ParseTree::Elementals { elementals } => elementals
+                    .iter()
+                    .skip(1)
+                    .try_fold(Number::default(), |sum, e| {
+                        add(sum, weigh(e, hydrogen)?, hydrogen)
+                    })?,
+                _ => Number::default(),
+            };
+            let n = weigh(numerator, hydrogen)?;
+            divide(n, d, hydrogen)
+        }
+        ParseTree::ElementalBooleanLiteral { val, .. } => Ok(Value::Boolean(!only_hydrogen(val))),
+        _ => Err(unsupported(literal, Token::default())),
+    }
+}
+
+fn number_token(number: &ParseTree, hydrogen: &Token) -> Result<Number, RuntimeError> {
+    match number {
+        ParseTree::Number { val } => match &val.token {
+            Type::Number(digits) => number::parse(digits).ok_or_else(|| {
+                RuntimeError::new(
+                    Reason::Value(ValueError::Overflow(digits.to_string())),
+                    val.clone(),
+                )
+            }),
+            _ => Err(unsupported(number, val.clone())),
+        },
+        _ => Err(unsupported(number, hydrogen.clone())),
+    }
+}
+
+fn divide(n: Number, d: Number, hydrogen: &Token) -> Result<Value, RuntimeError> {
+    Value::from(n)
+        .checked_div(Value::from(d))
+        .map_err(|err| RuntimeError::new(Reason::Value(err), hydrogen.clone()))
+}
+
+fn add(l: Number, r: Number, hydrogen: &Token) -> Result<Number, RuntimeError> {
+    number::checked_add(&l, &r).ok_or_else(|| {
+        RuntimeError::new(
+            Reason::Value(ValueError::Overflow(format!("{l} + {r}"))),
+            hydrogen.clone(),
+        )
+    })
+}
+
+fn mul(l: Number, r: usize, hydrogen: &Token) -> Result<Number, RuntimeError> {
+    number::checked_from_usize(r)
+        .and_then(|times| number::checked_mul(&l, &times))
+        .ok_or_else(|| {
+            RuntimeError::new(
+                Reason::Value(ValueError::Overflow(format!("{l} * {r}"))),
+                hydrogen.clone(),
+            )
+        })
+}
+
+// the sum of atomic numbers of the elements written in an elemental literal
+fn weigh(tree: &ParseTree, hydrogen: &Token) -> Result<Number, RuntimeError> {
+    match tree {
+        ParseTree::Compound { coeff, elementals } => mul(
+            weigh(elementals, hydrogen)?,
+            count(coeff.as_ref())?,
+            hydrogen,
+        ),
+        ParseTree::Elementals { elementals } => {
+            elementals.iter().try_fold(Number::default(), |sum, e| {
+                add(sum, weigh(e, hydrogen)?, hydrogen)
+            })
+        }
+        ParseTree::Periodic { element, subscript } => {
+            let times = match subscript.as_ref() {
+                Some(ParseTree::Subscript { val, .. }) => count(Some(val))?,
+                _ => 1,
+            };
+            mul(weigh(element, hydrogen)?, times, hydrogen)
+        }
+        ParseTree::Element { val } => {
+            let Type::Element(symbol) = &val.token else {
+                return Err(unsupported(tree, val.clone()));
+            };
+            Element::from_str(symbol)
+                .map_err(|err| RuntimeError::new(Reason::Element(err), val.clone()))?
+                .checked_atomic_numbers()
+                .map_err(|err| RuntimeError::new(Reason::Value(err), val.clone()))
+        }
+        _ => Err(unsupported(tree, hydrogen.clone())),
+    }
+}
+
+// whether an elemental literal is nothing but hydrogen
+fn only_hydrogen(tree: &ParseTree) -> bool {
+    match tree {
+        ParseTree::Compound { elementals, .. } => only_hydrogen(elementals),
+        ParseTree::Elementals { elementals } => elementals.iter().all(only_hydrogen),
+        ParseTree::Periodic { element, .. } => only_hydrogen(element),
+        ParseTree::Element { val } => val.token == Type::Element(String::from("H")),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // what a program prints, or the error it stops with
+    fn run(source: &str) -> Result<String, Error> {
+        let mut out = Vec::new();
+        Interpreter::new(&mut out).run(source)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    fn runtime_error(source: &str) -> Reason {
+        match run(source) {
+            Err(Error::Runtime(err)) => *err.reason,
+            ran => panic!("expected a runtime error, got {ran:?}"),
+        }
+    }
+
+    #[test]
+    fn prints_bonded_groups() {
+        assert_eq!(run("H^3Xn -> H\n").unwrap(), "3\n");
+        assert_eq!(run("H^1Kr + H^2Xn -> H\n").unwrap(), "11\n2\n");
+        assert_eq!(run("3H^1 + Xn -> H\n").unwrap(), "1\n1\n1\n");
+    }
+
+    #[test]
+    fn prints_strings() {
+        assert_eq!(
+            run("H\"Hello, World!\" + Xn -> H\n").unwrap(),
+            "Hello, World!\n"
+        );
+        assert_eq!(run("H\"a\\\"b\" + Xn -> H\n").unwrap(), "a\"b\n");
+    }
+
+    #[test]
+    fn weighs_antimatter_negatively() {
+        assert_eq!(run("HO-Fe + Xn -> H\n").unwrap(), "-26\n");
+        assert_eq!(run("HO-Uue + Xn -> H\n").unwrap(), "-119\n");
+        assert!(matches!(run("HO--Fe + Xn -> H\n"), Err(Error::Parse(_))));
+    }
+
+    #[test]
+    fn prints_formulas() {
+        assert_eq!(
+            run("H$\"CC\" + H$\"O[Uue]\" + Fm -> H\n").unwrap(),
+            "C2H6 30.070 g/mol\nHOUue (no molar mass for undiscovered Uue)\n"
+        );
+    }
+
+    #[test]
+    fn weighs_the_most_negative_number() {
+        assert_eq!(
+            run("H^-9223372036854775808 + Xn -> H\n").unwrap(),
+            "-9223372036854775808\n"
+        );
+    }
+
+    #[cfg(not(feature = "bigint"))]
+    #[test]
+    fn rejects_esters_too_heavy_to_weigh() {
+        assert!(matches!(
+            runtime_error(
+                "H$\"C(O)(O[Li])([Oeeeeeeeeeeeeeeeeee])[Oeeeeeeeeeeeeeeeeee]\" + Xn -> H\n"
+            ),
+            Reason::Value(ValueError::Overflow(_))
+        ));
+    }
+
+    #[test]
+    fn subscripts_are_not_truncated() {
+        // 4294967297 is 2^32 + 1, which used to multiply as 1
+        assert_eq!(
+            run("HOFe_4294967297 + Xn -> H\n").unwrap(),
+            "111669149722\n"
+        );
+    }
+
+    #[cfg(not(feature = "bigint"))]
+    #[test]
+    fn subscripts_overflow() {
+        assert!(matches!(
+            runtime_error("HOFe_999999999999999999 + Xn -> H\n"),
+            Reason::Value(ValueError::Overflow(_))
+        ));
+    }
+
+    #[test]
+    fn divides_the_last_two_groups() {
+        assert_eq!(run("H^6H^3DbXn -> H\n").unwrap(), "2\n");
+        assert_eq!(run("H^1H^3DbXn -> H\n").unwrap(), "1/3\n");
+        assert!(matches!(
+            runtime_error("H^2H^0DbXn -> H\n"),
+            Reason::Value(ValueError::DivisionByZero(_))
+        ));
+        assert!(matches!(
+            runtime_error("H^2DbXn -> H\n"),
+            Reason::Arity {
+                arity: 2,
+                bonded: 1,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn unknown_instructions_stop_the_run() {
+        let mut out = Vec::new();
+        let ran = Interpreter::new(&mut out).run("H^1Xn + Zz + Xn -> H\n");
+        assert!(matches!(
+            ran,
+            Err(Error::Runtime(err)) if matches!(*err.reason, Reason::UnknownInstruction(ref s) if s == "Zz")
+        ));
+        // everything before the error is still printed
+        assert_eq!(out, b"1\n");
+    }
+
+    #[test]
+    fn calls_host_functions() {
+        let mut out = Vec::new();
+        let mut interpreter = Interpreter::new(&mut out);
+        interpreter
+            .register("Ubn_2", |args| match args.as_slice() {
+                [Value::Number(l), Value::Number(r)] => Ok(Value::from(l * r)),
+                _ => Err(String::from("expected numbers")),
+            })
+            .unwrap();
+        interpreter
+            .register("Ubu_1", |_| Err(String::from("nope")))
+            .unwrap();
+        interpreter.run("H^6H^7UbnXn -> H\n").unwrap();
+        assert!(matches!(
+            interpreter.run("UbuXn -> H\n"),
+            Err(Error::Runtime(err)) if matches!(*err.reason, Reason::Host { ref err, .. } if err == "nope")
+        ));
+        // `Ubu` took the 42, so only the new group is bonded
+        assert!(matches!(
+            interpreter.run("H^1Ubn -> H\n"),
+            Err(Error::Runtime(err)) if matches!(*err.reason, Reason::Arity { arity: 2, bonded: 1, .. })
+        ));
+        drop(interpreter);
+        assert_eq!(out, b"42\n");
+    }
+
+    #[test]
+    fn calls_hosts_bound_to_discovered_elements() {
+        let mut out = Vec::new();
+        let mut interpreter = Interpreter::new(&mut out);
+        interpreter
+            .register("Uuq_2", |args| match args.as_slice() {
+                [Value::Number(l), Value::Number(r)] => Ok(Value::from(l - r)),
+                _ => Err(String::from("expected numbers")),
+            })
+            .unwrap();
+        interpreter.run("H^7H^2UuqXn -> H\n").unwrap();
+        drop(interpreter);
+        assert_eq!(out, b"5\n");
+    }
+
+    #[test]
+    fn moves_along_the_backbone() {
+        assert_eq!(
+            run("H^1KBe + NaBe + H^2Xn + NaLi + Xn -> H\n").unwrap(),
+            "2\n1\n"
+        );
+        // adding before the current carbon keeps it current, and undirected moves go down
+        assert_eq!(run("H^1KLi + NaLi + H^2 + Na + Xn -> H\n").unwrap(), "1\n");
+        assert_eq!(run("K_2 + NaBe_2 + H^3Xn -> H\n").unwrap(), "3\n");
+        assert!(matches!(
+            runtime_error("KNaBe_2 -> H\n"),
+            Reason::OffTape(Direction::Down)
+        ));
+        assert!(matches!(
+            runtime_error("NaLi -> H\n"),
+            Reason::OffTape(Direction::Up)
+        ));
+    }
+
+    #[test]
+    fn only_some_instructions_take_directions() {
+        assert!(matches!(
+            runtime_error("H^1XnBe -> H\n"),
+            Reason::Direction { symbol, direction: Direction::Down } if symbol == "Xn"
+        ));
+        // a subscript between them leaves the direction on its own
+        assert!(matches!(
+            runtime_error("K_2Be -> H\n"),
+            Reason::UnknownInstruction(symbol) if symbol == "Be"
+        ));
+        assert!(matches!(
+            runtime_error("Li -> H\n"),
+            Reason::UnknownInstruction(symbol) if symbol == "Li"
+        ));
+    }
+
+    #[test]
+    fn calls_named_equations() {
+        assert_eq!(
+            run("Uue: H^1 -> H\n2Uue + Uue_2 + Uuo -> H\nUuo: H^2Xn -> H\n").unwrap(),
+            "1\n1\n1\n1\n2\n"
+        );
+        assert_eq!(run("Uuq: H^4Xn -> H\nUuq -> H\n").unwrap(), "4\n");
+        assert!(matches!(
+            runtime_error("Uue: Uuo -> H\nUuo: Uue -> H\nUue -> H\n"),
+            Reason::Depth { symbol, depth: MAX_DEPTH } if symbol == "Uuo" || symbol == "Uue"
+        ));
+    }
+
+    #[test]
+    fn names_equations_like_hosts() {
+        assert!(matches!(
+            runtime_error("Fe: H^1Xn -> H\nFe -> H\n"),
+            Reason::Definition(BindError::Reserved(symbol)) if symbol == "Fe"
+        ));
+        assert!(matches!(
+            runtime_error("H^1Xn -> H\nUue: H^1 -> H\nUue: H^2 -> H\n"),
+            Reason::Redefined(symbol) if symbol == "Uue"
+        ));
+        // before anything runs
+        assert!(matches!(
+            run("H^1Xn -> H\nFe: H^1 -> H\n"),
+            Err(Error::Runtime(_))
+        ));
+
+        let mut out = Vec::new();
+        let mut interpreter = Interpreter::new(&mut out);
+        interpreter.register("Ubn", |_| Ok(Value::from(1))).unwrap();
+        interpreter.run("Ubn: H^2 -> H\nUbnXn -> H\n").unwrap();
+        drop(interpreter);
+        assert_eq!(out, b"2\n");
+    }
+
+    #[test]
+    fn resumes_inside_calls() {
+        let source = "Uue: H^1BkXn -> H\nUue + NaBe -> H\n";
+        let mut out = Vec::new();
+        let mut interpreter = Interpreter::new(&mut out).with_snapshots();
+        assert!(interpreter.run(source).is_err());
+        let snapshot = interpreter.snapshots.as_ref().unwrap()[0].clone();
+        assert_eq!(snapshot.step, 2);
+        interpreter.restore(&snapshot);
+        assert!(interpreter.run(source).is_err());
+        drop(interpreter);
+        assert_eq!(out, b"1\n1\n");
+    }
+
+    #[test]
+    fn resumes_from_snapshots() {
+        let mut out = Vec::new();
+        let mut interpreter = Interpreter::new(&mut out).with_snapshots();
+        interpreter.run("H^1Bk + H^2Xn -> H\n").unwrap();
+        let snapshot = interpreter.snapshots.as_ref().unwrap()[0].clone();
+        assert_eq!(snapshot.step, 2);
+        interpreter.tape = Alkane::new();
+        interpreter.restore(&snapshot);
+        interpreter.run("H^1Bk + H^2Xn -> H\n").unwrap();
+        drop(interpreter);
+        assert_eq!(out, b"1\n2\n1\n2\n");
+    }
+
+    #[test]
+    fn resumes_only_the_snapshots_program() {
+        let source = "H^1Bk + H^2Xn -> H\n";
+        let mut out = Vec::new();
+        let mut interpreter = Interpreter::new(&mut out).with_snapshots();
+        interpreter.run(source).unwrap();
+        let snapshot = interpreter.snapshots.as_ref().unwrap()[0].clone();
+        interpreter.restore(&snapshot);
+        let err = interpreter.run("H^3Xn -> H\n").unwrap_err();
+        assert!(matches!(err, Error::Runtime(err) if matches!(*err.reason, Reason::Resume)));
+        // the snapshot is still there to resume
+        interpreter.walk(source).unwrap();
+        drop(interpreter);
+        assert_eq!(out, b"1\n2\n1\n2\n");
+    }
+
+    #[test]
+    fn snapshots_round_trip() {
+        let mut interpreter = Interpreter::new(Vec::new()).with_snapshots();
+        interpreter.run("H^1 + H$\"O[Li]\"Bk -> H\n").unwrap();
+        let snapshot = &interpreter.snapshots.as_ref().unwrap()[0];
+        let read: Snapshot = snapshot.to_string().parse().unwrap();
+        assert_eq!(read.source, snapshot.source);
+        assert_eq!(read.step, 3);
+        assert_eq!(read.tape.value(), snapshot.tape.value());
+        assert!(!snapshot.to_string().contains("written"));
+    }
+}
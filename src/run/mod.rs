@@ -0,0 +1,6 @@
+pub mod bytecode;
+pub mod host;
+pub mod interpreter;
+pub mod runtime_error;
+pub mod snapshot;
+pub mod tape;
@@ -0,0 +1,113 @@
+use std::fmt::Display;
+
+use crate::{
+    eval::{element::ElementError, value::ValueError},
+    import::smiles::SmilesError,
+    lex::tok::Token,
+};
+
+use super::{bytecode::Direction, host::BindError};
+
+#[derive(Debug, Clone)]
+pub struct RuntimeError {
+    // boxed so results carrying a runtime error stay small
+    pub reason: Box<Reason>,
+    // the token of the instruction or literal that failed
+    pub token: Token,
+}
+
+impl RuntimeError {
+    pub fn new(reason: Reason, token: Token) -> RuntimeError {
+        RuntimeError {
+            reason: Box::new(reason),
+            token,
+        }
+    }
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("RuntimeError: {}\n", self.reason))?;
+        f.write_fmt(format_args!("    at: {}", self.token))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Reason {
+    UnknownInstruction(String),
+    // parsed, but not something the interpreter can do yet
+    Unsupported(String),
+    Value(ValueError),
+    Element(ElementError),
+    Smiles(SmilesError),
+    Io(String),
+    Save(String),
+    // a direction written after an instruction that doesn't take one
+    Direction {
+        symbol: String,
+        direction: Direction,
+    },
+    // moving past the end of the backbone, which is a crash
+    OffTape(Direction),
+    // an equation named something that can't be bound, or named twice
+    Definition(BindError),
+    Redefined(String),
+    // calls into definitions nested deeper than the interpreter allows,
+    // which is what a definition calling itself always ends in, since nothing can stop it
+    Depth {
+        symbol: String,
+        depth: usize,
+    },
+    // running a program other than the one a restored snapshot was taken from
+    Resume,
+    // a host function called with fewer groups bonded than it takes
+    Arity {
+        symbol: String,
+        arity: usize,
+        bonded: usize,
+    },
+    // an error returned by a host function
+    Host {
+        symbol: String,
+        err: String,
+    },
+}
+
+impl Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reason::UnknownInstruction(symbol) => write!(f, "unknown instruction `{symbol}`"),
+            Reason::Unsupported(what) => write!(f, "not supported yet: {what}"),
+            Reason::Value(err) => write!(f, "{err}"),
+            Reason::Element(err) => write!(f, "{err}"),
+            Reason::Smiles(err) => write!(f, "{err}"),
+            Reason::Io(err) => write!(f, "could not write output: {err}"),
+            Reason::Save(err) => write!(f, "could not save a snapshot: {err}"),
+            Reason::Direction { symbol, direction } => {
+                write!(f, "`{symbol}` doesn't take a direction, but has `{direction}`")
+            }
+            Reason::OffTape(direction) => {
+                write!(f, "moved off the alkane, with no carbon towards `{direction}`")
+            }
+            Reason::Definition(err) => write!(f, "can't name an equation: {err}"),
+            Reason::Redefined(symbol) => write!(f, "`{symbol}` names more than one equation"),
+            Reason::Depth { symbol, depth } => write!(
+                f,
+                "calling `{symbol}` nests calls more than {depth} deep"
+            ),
+            Reason::Resume => write!(
+                f,
+                "the restored snapshot was taken from a different program, so its steps can't be skipped"
+            ),
+            Reason::Arity {
+                symbol,
+                arity,
+                bonded,
+            } => write!(
+                f,
+                "`{symbol}` takes {arity} bonded groups, but the current carbon has {bonded}"
+            ),
+            Reason::Host { symbol, err } => write!(f, "`{symbol}` failed: {err}"),
+        }
+    }
+}
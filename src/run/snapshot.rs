@@ -0,0 +1,96 @@
+// the state of a run at a step, to resume from later or rewind to.
+// a snapshot holds the program it was taken from, so resuming only needs the snapshot.
+// programs have no input or branching yet, so running the same program again
+// reaches the same step the same way, and the steps before it can be skipped.
+// skipped steps don't run, so nothing printed before the snapshot is printed again.
+//
+// written as a version header line, then JSON, with the tape in the form `export::json` writes:
+// `er snapshot 1\n{"source": "...", "step": 3, "tape": {...}}`
+
+use std::{fmt::Display, str::FromStr};
+
+use serde_json::{json, Value as Json};
+
+use crate::{
+    eval::functional_groups::{alkane::Alkane, FunctionalGroup},
+    import::json::{shape, JsonError},
+};
+
+const HEADER: &str = "er snapshot";
+const VERSION: u32 = 1;
+
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub source: String,
+    // the number of instructions run and literals bonded so far
+    pub step: usize,
+    pub tape: Alkane,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotError {
+    // not a snapshot, or one from a version this can't read
+    Header(String),
+    Json(JsonError),
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::Header(header) => write!(
+                f,
+                "expected a snapshot starting with `{HEADER} {VERSION}`, got `{header}`"
+            ),
+            SnapshotError::Json(err) => write!(f, "invalid snapshot: {err}"),
+        }
+    }
+}
+
+impl Display for Snapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let json = json!({
+            "source": self.source,
+            "step": self.step,
+            "tape": FunctionalGroup::Alkane(self.tape.clone()).to_json(),
+        });
+        writeln!(f, "{HEADER} {VERSION}")?;
+        writeln!(f, "{json}")
+    }
+}
+
+impl FromStr for Snapshot {
+    type Err = SnapshotError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (header, json) = s.split_once('\n').unwrap_or((s, ""));
+        if header.trim_end() != format!("{HEADER} {VERSION}") {
+            return Err(SnapshotError::Header(header.to_string()));
+        }
+        let json: Json = serde_json::from_str(json)
+            .map_err(|err| SnapshotError::Json(JsonError::Syntax(err.to_string())))?;
+        let field = |name: &str| json.get(name).unwrap_or(&Json::Null);
+        let count = |name: &str| {
+            let count = field(name);
+            count
+                .as_u64()
+                .map(|n| n as usize)
+                .ok_or_else(|| SnapshotError::Json(shape("a count", count)))
+        };
+        let source = field("source");
+        let Some(source) = source.as_str() else {
+            return Err(SnapshotError::Json(shape(
+                "the source of a program",
+                source,
+            )));
+        };
+        let tape = match FunctionalGroup::from_json(field("tape")).map_err(SnapshotError::Json)? {
+            FunctionalGroup::Alkane(tape) => tape,
+            _ => return Err(SnapshotError::Json(shape("an alkane", field("tape")))),
+        };
+        Ok(Snapshot {
+            source: source.to_string(),
+            step: count("step")?,
+            tape,
+        })
+    }
+}
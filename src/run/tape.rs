@@ -0,0 +1,378 @@
+// the operations the interpreter runs against its tape, so it can keep the tape in different forms.
+// `Alkane` is the tape as a molecule, which is what gets inspected, exported and saved,
+// and `CompactTape` keeps just what a run needs, converting to and from an `Alkane` for everything else.
+
+use crate::eval::{
+    functional_groups::{
+        alkane::{Alkane, Container},
+        FunctionalGroup,
+    },
+    molecule::Molecule,
+};
+
+pub trait Tape: Clone {
+    // the functional groups bonded to the current carbon, in the order they were bonded
+    fn bonded_groups(&self) -> Vec<&FunctionalGroup>;
+    fn add_functional_group(&mut self, fg: FunctionalGroup);
+    // unbonds the last `n` functional groups bonded to the current carbon,
+    // in the order they were bonded, or `None` if there aren't that many
+    fn take_bonded_groups(&mut self, n: usize) -> Option<Vec<FunctionalGroup>>;
+
+    // adds an empty carbon next to the current one, which stays current
+    fn add_carbon_after(&mut self);
+    fn add_carbon_before(&mut self);
+    // moves to the next carbon along, returning whether there was one
+    fn move_up(&mut self) -> bool;
+    fn move_down(&mut self) -> bool;
+
+    fn to_alkane(&self) -> Alkane;
+    fn from_alkane(alkane: Alkane) -> Self;
+}
+
+impl Tape for Alkane {
+    fn bonded_groups(&self) -> Vec<&FunctionalGroup> {
+        self.get_currently_bonded_molecules()
+            .into_iter()
+            .filter_map(|m| match m {
+                Molecule::F(fg) => Some(fg),
+                Molecule::E(_) => None,
+            })
+            .collect()
+    }
+
+    fn add_functional_group(&mut self, fg: FunctionalGroup) {
+        Alkane::add_functional_group(self, fg)
+    }
+
+    fn take_bonded_groups(&mut self, n: usize) -> Option<Vec<FunctionalGroup>> {
+        Alkane::take_bonded_groups(self, n)
+    }
+
+    fn add_carbon_after(&mut self) {
+        Alkane::add_carbon_after(self)
+    }
+
+    fn add_carbon_before(&mut self) {
+        Alkane::add_carbon_before(self)
+    }
+
+    fn move_up(&mut self) -> bool {
+        Alkane::move_up(self)
+    }
+
+    fn move_down(&mut self) -> bool {
+        Alkane::move_down(self)
+    }
+
+    fn to_alkane(&self) -> Alkane {
+        self.clone()
+    }
+
+    fn from_alkane(alkane: Alkane) -> Self {
+        alkane
+    }
+}
+
+// the tape as the molecules bonded to each backbone carbon, in backbone order,
+// with the current carbon as an index into them, so moving is a step instead of a search
+#[derive(Debug, Clone)]
+pub struct CompactTape {
+    carbons: Vec<Vec<Molecule>>,
+    current: usize,
+    container: Container,
+}
+
+impl CompactTape {
+    pub fn new() -> CompactTape {
+        CompactTape {
+            carbons: vec![Vec::new()],
+            current: 0,
+            container: Container::List,
+        }
+    }
+}
+
+impl Default for CompactTape {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Tape for CompactTape {
+    fn bonded_groups(&self) -> Vec<&FunctionalGroup> {
+        self.carbons[self.current]
+            .iter()
+            .filter_map(|m| match m {
+                Molecule::F(fg) => Some(fg),
+                Molecule::E(_) => None,
+            })
+            .collect()
+    }
+
+    fn add_functional_group(&mut self, fg: FunctionalGroup) {
+        self.carbons[self.current].push(Molecule::F(fg));
+    }
+
+    fn take_bonded_groups(&mut self, n: usize) -> Option<Vec<FunctionalGroup>> {
+        let bonded = &mut self.carbons[self.current];
+        let groups: Vec<usize> = (0..bonded.len())
+            .filter(|i| matches!(bonded[*i], Molecule::F(_)))
+            .collect();
+        let start = groups.len().checked_sub(n)?;
+        // elements bonded between the groups stay where they are
+        let taken = groups[start..]
+            .iter()
+            .rev()
+            .map(|i| match bonded.remove(*i) {
+                Molecule::F(fg) => fg,
+                Molecule::E(_) => unreachable!(),
+            })
+            .collect::<Vec<FunctionalGroup>>();
+        Some(taken.into_iter().rev().collect())
+    }
+
+    fn add_carbon_after(&mut self) {
+        self.carbons.insert(self.current + 1, Vec::new());
+    }
+
+    fn add_carbon_before(&mut self) {
+        self.carbons.insert(self.current, Vec::new());
+        self.current += 1;
+    }
+
+    fn move_up(&mut self) -> bool {
+        match self.current {
+            0 => false,
+            _ => {
+                self.current -= 1;
+                true
+            }
+        }
+    }
+
+    fn move_down(&mut self) -> bool {
+        match self.current + 1 < self.carbons.len() {
+            true => {
+                self.current += 1;
+                true
+            }
+            false => false,
+        }
+    }
+
+    // the carbons are added before anything bonded to them, so bond order comes out the same
+    fn to_alkane(&self) -> Alkane {
+        let mut alkane = Alkane::new_n_alkane(self.carbons.len() - 1);
+        let backbone: Vec<_> = alkane.backbone().copied().collect();
+        for (carbon, molecules) in backbone.iter().zip(&self.carbons) {
+            alkane.current_atom = *carbon;
+            molecules
+                .iter()
+                .for_each(|m| alkane.add_molecule(m.clone()));
+        }
+        alkane.current_atom = backbone[self.current];
+        alkane.set_container(self.container);
+        alkane
+    }
+
+    fn from_alkane(alkane: Alkane) -> Self {
+        let mut carbons: Vec<Vec<Molecule>> = alkane
+            .backbone()
+            .map(|carbon| {
+                alkane
+                    .get_bonded_molecules(*carbon)
+                    .into_iter()
+                    .cloned()
+                    .collect()
+            })
+            .collect();
+        // an alkane built without a backbone still leaves a carbon to be on, like `CompactTape::new`
+        if carbons.is_empty() {
+            carbons.push(Vec::new());
+        }
+        CompactTape {
+            current: alkane
+                .backbone()
+                .position(|carbon| *carbon == alkane.current_atom)
+                .unwrap_or_default(),
+            carbons,
+            container: alkane.container(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use petgraph::stable_graph::NodeIndex;
+
+    use super::*;
+    use crate::eval::{atoms::Atoms, traits::Valuable, value::Value};
+
+    #[derive(Debug, Clone, Copy)]
+    enum Op {
+        Bond(i64),
+        Take(usize),
+        After,
+        Before,
+        Up,
+        Down,
+    }
+
+    // what a tape looks like: the current carbon's groups, the whole tape's value,
+    // and its SMILES, which follows the bonds between carbons rather than the order of the backbone
+    fn state<T: Tape>(tape: &T) -> (Vec<Value>, Value, String) {
+        let bonded = tape.bonded_groups().iter().map(|fg| fg.value()).collect();
+        let alkane = tape.to_alkane();
+        (bonded, alkane.value(), alkane.to_smiles())
+    }
+
+    // runs the same ops on both tapes, checking they agree after every one
+    fn same(ops: &[Op]) {
+        let (mut alkane, mut compact) = (Alkane::new(), CompactTape::new());
+        for (i, op) in ops.iter().enumerate() {
+            let (l, r) = match *op {
+                Op::Bond(n) => {
+                    let fg = FunctionalGroup::from(Value::from(n));
+                    alkane.add_functional_group(fg.clone());
+                    compact.add_functional_group(fg);
+                    (true, true)
+                }
+                Op::Take(n) => {
+                    let values = |groups: Option<Vec<FunctionalGroup>>| {
+                        groups.map(|groups| groups.iter().map(|fg| fg.value()).collect::<Vec<_>>())
+                    };
+                    let (l, r) = (
+                        values(Tape::take_bonded_groups(&mut alkane, n)),
+                        values(compact.take_bonded_groups(n)),
+                    );
+                    assert_eq!(l, r, "taking {n} after {:?}", &ops[..i]);
+                    (l.is_some(), r.is_some())
+                }
+                Op::After => {
+                    Tape::add_carbon_after(&mut alkane);
+                    compact.add_carbon_after();
+                    (true, true)
+                }
+                Op::Before => {
+                    Tape::add_carbon_before(&mut alkane);
+                    compact.add_carbon_before();
+                    (true, true)
+                }
+                Op::Up => (Tape::move_up(&mut alkane), compact.move_up()),
+                Op::Down => (Tape::move_down(&mut alkane), compact.move_down()),
+            };
+            assert_eq!(l, r, "{op:?} after {:?}", &ops[..i]);
+            assert_eq!(state(&alkane), state(&compact), "after {:?}", &ops[..=i]);
+            // and converting doesn't lose anything
+            assert_eq!(
+                state(&CompactTape::from_alkane(alkane.clone())),
+                state(&alkane)
+            );
+            assert_eq!(state(&compact.to_alkane()), state(&compact));
+        }
+    }
+
+    #[test]
+    fn adding_carbons_keeps_the_current_one() {
+        use Op::*;
+        same(&[Bond(1), After, Bond(2), Down, Bond(3), Up, Up, Bond(4)]);
+        same(&[Bond(1), Before, Bond(2), Up, Bond(3), Down, Down, Bond(4)]);
+        // in the middle of the backbone
+        same(&[
+            After,
+            After,
+            Down,
+            Bond(1),
+            After,
+            Before,
+            Bond(2),
+            Down,
+            Bond(3),
+            Up,
+            Up,
+            Bond(4),
+        ]);
+        same(&[
+            Before,
+            Before,
+            Up,
+            Before,
+            Up,
+            Up,
+            Bond(1),
+            Down,
+            Down,
+            Down,
+            Down,
+            Bond(2),
+        ]);
+    }
+
+    #[test]
+    fn moving_stops_at_the_ends() {
+        use Op::*;
+        same(&[Up, Down, After, Down, Down, Up, Up, Up]);
+    }
+
+    #[test]
+    fn takes_the_same_groups() {
+        use Op::*;
+        same(&[
+            Bond(1),
+            Bond(2),
+            Bond(3),
+            Take(2),
+            Bond(4),
+            Take(3),
+            Take(2),
+            Bond(5),
+            Take(0),
+        ]);
+        same(&[
+            Bond(1),
+            After,
+            Down,
+            Bond(2),
+            Bond(3),
+            Take(2),
+            Up,
+            Take(1),
+            Bond(4),
+            Down,
+            Bond(5),
+        ]);
+    }
+
+    #[test]
+    fn converts_alkanes_without_a_backbone() {
+        let alkane = Alkane::from_parts(Atoms::new(), NodeIndex::default(), VecDeque::new(), None);
+        let mut compact = CompactTape::from_alkane(alkane);
+        assert_eq!(state(&compact.to_alkane()), state(&CompactTape::new()));
+        compact.add_functional_group(FunctionalGroup::from(Value::from(1)));
+        assert_eq!(state(&compact).0, [Value::from(1)]);
+    }
+
+    #[test]
+    fn agree_on_long_runs() {
+        // a fixed linear congruential generator, so failures can be reproduced
+        let mut seed: u64 = 0x2545_f491;
+        let mut next = move |n: u64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) % n
+        };
+        let ops: Vec<Op> = (0..150)
+            .map(|i| match next(6) {
+                0 | 1 => Op::Bond(i),
+                2 => Op::Take(next(3) as usize),
+                3 => [Op::After, Op::Before][next(2) as usize],
+                4 => Op::Up,
+                _ => Op::Down,
+            })
+            .collect();
+        same(&ops);
+    }
+}
@@ -4,23 +4,198 @@ use std::{
     path::PathBuf,
 };
 
-use clap::{ArgGroup, Parser};
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+
+use esoteric_reaction::Snapshot;
 
 #[derive(Debug, Parser)]
 #[command(name = "EsotericReaction")]
 #[command(author = "bigyhsuan")]
 #[command(version = "0.0.0")]
-#[clap(group(ArgGroup::new("source").required(false).multiple(false).args(&["file", "code"])))]
+#[command(args_conflicts_with_subcommands = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    source: Source,
+}
+
+#[derive(Debug, clap::Args)]
+#[clap(group(ArgGroup::new("source").required(false).multiple(false).args(&["file", "code"])))]
+struct Source {
     #[arg(short, long, value_name = "FILE")]
     file: Option<PathBuf>,
     #[arg(short, long, value_name = "CODE")]
     code: Option<String>,
 }
 
-pub fn parse_args() -> String {
+#[derive(Debug, Subcommand)]
+enum Command {
+    #[command(about = "Run a program")]
+    Run {
+        #[command(flatten)]
+        source: Source,
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Write the tape as Graphviz DOT at every `Bk` breakpoint and at the end"
+        )]
+        dump_dot: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Write the groups bonded to the current carbon at the end as Mermaid diagrams"
+        )]
+        dump_mermaid: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Write the tape as SMILES at the end"
+        )]
+        dump_smiles: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Bond the values or functional groups in a JSON array to the current carbon before running"
+        )]
+        input_json: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Write the groups bonded to the current carbon at the end as a JSON array"
+        )]
+        output_json: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Save a snapshot of the run at every `Bk` breakpoint, to continue from with `--resume`"
+        )]
+        save: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "FILE",
+            conflicts_with_all = ["file", "code", "input_json"],
+            help = "Continue the run saved in a snapshot"
+        )]
+        resume: Option<PathBuf>,
+        #[arg(
+            long,
+            help = "Run with the tree-walking interpreter instead of compiling to bytecode, to check one against the other"
+        )]
+        tree_walk: bool,
+        #[arg(
+            long,
+            help = "Keep the tape in a compact form while running, only building the molecule to dump or save it"
+        )]
+        compact_tape: bool,
+    },
+    #[command(about = "Compile a program to source code for another language")]
+    Build {
+        #[arg(long, value_enum, help = "The language to compile to")]
+        target: Target,
+        #[arg(
+            value_name = "FILE",
+            help = "The program to compile, or stdin if missing"
+        )]
+        file: Option<PathBuf>,
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            help = "Where to write the compiled program, or stdout if missing"
+        )]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Target {
+    // a standalone C program
+    C,
+}
+
+pub enum Task {
+    Run(Box<Options>),
+    Build(Build),
+}
+
+pub struct Options {
+    pub source: String,
+    pub dump_dot: Option<PathBuf>,
+    pub dump_mermaid: Option<PathBuf>,
+    pub dump_smiles: Option<PathBuf>,
+    pub input_json: Option<PathBuf>,
+    pub output_json: Option<PathBuf>,
+    pub save: Option<PathBuf>,
+    pub resume: Option<Snapshot>,
+    pub tree_walk: bool,
+    pub compact_tape: bool,
+}
+
+pub struct Build {
+    pub source: String,
+    pub target: Target,
+    pub output: Option<PathBuf>,
+}
+
+pub fn parse_args() -> Task {
     let args = Args::parse();
-    let source = match (args.file, args.code) {
+    match args.command {
+        Some(Command::Run {
+            source,
+            dump_dot,
+            dump_mermaid,
+            dump_smiles,
+            input_json,
+            output_json,
+            save,
+            resume,
+            tree_walk,
+            compact_tape,
+        }) => {
+            let resume = resume.map(read_snapshot);
+            Task::Run(Box::new(Options {
+                source: match &resume {
+                    Some(snapshot) => snapshot.source.clone(),
+                    None => read_source(source),
+                },
+                dump_dot,
+                dump_mermaid,
+                dump_smiles,
+                input_json,
+                output_json,
+                save,
+                resume,
+                tree_walk,
+                compact_tape,
+            }))
+        }
+        Some(Command::Build {
+            target,
+            file,
+            output,
+        }) => Task::Build(Build {
+            source: read_source(Source { file, code: None }),
+            target,
+            output,
+        }),
+        None => Task::Run(Box::new(Options {
+            source: read_source(args.source),
+            dump_dot: None,
+            dump_mermaid: None,
+            dump_smiles: None,
+            input_json: None,
+            output_json: None,
+            save: None,
+            resume: None,
+            tree_walk: false,
+            compact_tape: false,
+        })),
+    }
+}
+
+fn read_source(source: Source) -> String {
+    let source = match (source.file, source.code) {
         (None, Some(code)) => code,
         (Some(file), None) => fs::read_to_string(file)
             .unwrap_or_else(|err| panic!("could not read from stdin: {}", err)),
@@ -39,3 +214,10 @@ pub fn parse_args() -> String {
         source + "\n"
     }
 }
+
+fn read_snapshot(path: PathBuf) -> Snapshot {
+    fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("could not read from {}: {}", path.display(), err))
+        .parse()
+        .unwrap_or_else(|err| panic!("{}: {}", path.display(), err))
+}